@@ -0,0 +1,159 @@
+// Compare bitboard `Field` with the old `HashSet` based implementation.
+//
+// run: cargo bench --bench field
+use std::collections::HashSet;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use rs_1010::coord;
+use rs_1010::coord::Coord;
+use rs_1010::engine::{FIELD_LEN, Field, Figure, default_figures};
+use rs_1010::random::Random;
+
+const STATES: usize = 1000;
+const ROUNDS: usize = 5;
+
+// previous implementation of the field
+struct SetField {
+    field_size: Coord,
+    field: HashSet<Coord>,
+}
+
+impl SetField {
+    fn shift(figure: &[Coord], pos: Coord) -> HashSet<Coord> {
+        figure.iter().map(|p| *p + pos).collect()
+    }
+
+    fn can_set_figure(&self, pos: Coord, figure: &[Coord]) -> bool {
+        let new_figure = SetField::shift(figure, pos);
+        for Coord { x, y } in &new_figure {
+            if *x >= self.field_size.x || *x < 0 || *y >= self.field_size.y || *y < 0 {
+                return false;
+            }
+        }
+        self.field.intersection(&new_figure).count() == 0
+    }
+
+    fn can_set(&self, figures: &[Vec<Coord>]) -> bool {
+        for figure in figures {
+            let x_max = figure.iter().fold(0, |m, p| m.max(p.x));
+            let y_max = figure.iter().fold(0, |m, p| m.max(p.y));
+            for y in 0..self.field_size.y - y_max {
+                for x in 0..self.field_size.x - x_max {
+                    if self.field.intersection(&SetField::shift(figure, coord!(x, y))).count() == 0 {
+                        return true;
+                    }
+                }
+            }
+        }
+        false
+    }
+
+    fn full_lines(&self) -> u32 {
+        let mut lines = 0;
+        for i in 0..self.field_size.x {
+            let h = (0..self.field_size.x).all(|x| self.field.contains(&coord!(x, i)));
+            let v = (0..self.field_size.y).all(|y| self.field.contains(&coord!(i, y)));
+            lines += h as u32 + v as u32;
+        }
+        lines
+    }
+}
+
+fn random_states(rnd: &mut Random) -> Vec<Vec<Coord>> {
+    let mut states = Vec::with_capacity(STATES);
+    for _ in 0..STATES {
+        // from empty to almost full field
+        let density = rnd.rand() % 100;
+        let mut blocks = Vec::new();
+        for y in 0..FIELD_LEN as i16 {
+            for x in 0..FIELD_LEN as i16 {
+                if rnd.rand() % 100 < density {
+                    blocks.push(coord!(x, y));
+                }
+            }
+        }
+        states.push(blocks);
+    }
+    states
+}
+
+fn bench<F: FnMut() -> u32>(name: &str, mut f: F) -> Duration {
+    let start = Instant::now();
+    let mut result = 0;
+    for _ in 0..ROUNDS {
+        result += black_box(f());
+    }
+    let elapsed = start.elapsed();
+    println!("{name:>10}: {elapsed:>12.3?} (checksum {result})");
+    elapsed
+}
+
+fn main() {
+    let mut rnd = Random::new(1010);
+    let states = random_states(&mut rnd);
+    let figures = default_figures();
+    let figure_blocks: Vec<Vec<Coord>> = figures.iter().map(|f| f.iter().collect()).collect();
+
+    let bit_fields: Vec<Field> = states
+        .iter()
+        .map(|blocks| {
+            let mut field = Field::init_square(FIELD_LEN);
+            blocks.iter().for_each(|p| field.set(*p, 0));
+            field
+        })
+        .collect();
+    let set_fields: Vec<SetField> = states
+        .iter()
+        .map(|blocks| SetField { field_size: coord!(FIELD_LEN as i16), field: blocks.iter().cloned().collect() })
+        .collect();
+
+    let workload = |can_set: &dyn Fn(usize, &[usize]) -> bool, can_set_figure: &dyn Fn(usize, Coord, usize) -> bool| {
+        let mut counter = 0;
+        for state in 0..STATES {
+            // game over check on triples of figures
+            for index in (0..figures.len()).step_by(3) {
+                let triple: Vec<usize> = (index..(index + 3).min(figures.len())).collect();
+                counter += can_set(state, &triple) as u32;
+            }
+            // placement of every figure into every cell
+            for figure in 0..figures.len() {
+                for y in 0..FIELD_LEN as i16 {
+                    for x in 0..FIELD_LEN as i16 {
+                        counter += can_set_figure(state, coord!(x, y), figure) as u32;
+                    }
+                }
+            }
+        }
+        counter
+    };
+
+    println!("placements on {STATES} random fields x {ROUNDS} rounds");
+    let set_time = bench("hashset", || {
+        let lines: u32 = set_fields.iter().map(SetField::full_lines).sum();
+        lines
+            + workload(
+                &|state, triple| {
+                    let triple: Vec<Vec<Coord>> = triple.iter().map(|i| figure_blocks[*i].clone()).collect();
+                    set_fields[state].can_set(&triple)
+                },
+                &|state, pos, figure| set_fields[state].can_set_figure(pos, &figure_blocks[figure]),
+            )
+    });
+    let bit_time = bench("bitboard", || {
+        let lines: u32 = bit_fields
+            .iter()
+            .map(|field| field.clone().next_state().map(|(lines, _)| lines.x + lines.y).unwrap_or(0))
+            .sum();
+        lines
+            + workload(
+                &|state, triple| {
+                    let triple: Vec<Figure> = triple.iter().map(|i| figures[*i].clone()).collect();
+                    bit_fields[state].can_set(&triple)
+                },
+                &|state, pos, figure| bit_fields[state].can_set_figure(pos, &figures[figure]),
+            )
+    });
+
+    println!("   speedup: {:.1}x", set_time.as_secs_f64() / bit_time.as_secs_f64());
+}
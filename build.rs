@@ -1,6 +1,6 @@
 use std::collections::HashMap;
 use std::process::Command;
-use std::{fs::read_to_string, fs::File, io::Write};
+use std::{fs::File, fs::read_to_string, io::Write};
 
 // execute app and get stdout
 fn execute(cmd: &str, args: &[&str]) -> String {
@@ -44,13 +44,11 @@ fn app_packages() -> String {
             name = data.to_string();
             name_flag = true;
         }
-        if name_flag {
-            if let Some(data) = line.strip_prefix("version = ") {
-                name_flag = false;
-                // (name, version)
-                packages.push_str(&format!("    ({}, {}),\n", name, data));
-                counter += 1;
-            }
+        if name_flag && let Some(data) = line.strip_prefix("version = ") {
+            name_flag = false;
+            // (name, version)
+            packages.push_str(&format!("    ({}, {}),\n", name, data));
+            counter += 1;
         }
     }
 
@@ -0,0 +1,22 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use rs_1010::codec::Decoder;
+
+// bits are taken from any text until it is exhausted
+fuzz_target!(|data: &[u8]| {
+    let Ok(text) = std::str::from_utf8(data) else {
+        return;
+    };
+    let Ok(mut decoder) = Decoder::decode(text) else {
+        return;
+    };
+    // widths come from the text too, 65 is too wide
+    for width in text.bytes().map(|byte| byte % 66).cycle().take(text.len() * 2) {
+        let remaining = decoder.remaining_bits();
+        match decoder.take::<u64>(width) {
+            Ok(_) => assert_eq!(decoder.remaining_bits(), remaining - width as usize),
+            Err(_) => assert_eq!(decoder.remaining_bits(), remaining),
+        }
+    }
+});
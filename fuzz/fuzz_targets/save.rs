@@ -0,0 +1,34 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use rs_1010::coord;
+use rs_1010::engine::{GameEngine, default_figures};
+use rs_1010::save::{self, Assists};
+
+// any save slot or pasted position code is loaded or rejected
+fuzz_target!(|data: &[u8]| {
+    let Ok(text) = std::str::from_utf8(data) else {
+        return;
+    };
+    let mut practice = GameEngine::new(default_figures(), 1010);
+    if save::load_code(text, &mut practice).is_ok() {
+        let mut shared = GameEngine::new(default_figures(), 1);
+        save::load_code(&save::share_code(&practice), &mut shared).unwrap();
+        assert_eq!(shared.field().blocks(), practice.field().blocks());
+        for index in 0..practice.basket().len() {
+            let _ = practice.place(index, coord!(0, 0));
+        }
+    }
+    let mut engine = GameEngine::new(default_figures(), 1010);
+    let mut assists = Assists::default();
+    let Ok(elapsed) = save::deserialize(text, &mut engine, &mut assists) else {
+        return;
+    };
+    // loaded game is saved again and can be played
+    let mut restored = engine.clone();
+    save::deserialize(&save::serialize(&engine, elapsed, assists), &mut restored, &mut assists).unwrap();
+    assert_eq!(restored.field().blocks(), engine.field().blocks());
+    for index in 0..engine.basket().len() {
+        let _ = engine.place(index, coord!(0, 0));
+    }
+});
@@ -0,0 +1,262 @@
+use crate::coord::Coord;
+use crate::engine::{BasketSystem, Event, Field, Figure, GameEngine, Lines};
+use crate::hint::{self, Placement};
+
+// placements kept on every lookahead step
+const LOOKAHEAD_BEAM: usize = 4;
+// penalty for every figure which cannot be placed
+const DEAD_END_PENALTY: i32 = 1000;
+
+pub const STRATEGIES: [&str; 2] = ["greedy", "lookahead"];
+
+// result of the computer game
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct GameStats {
+    pub score: u32,
+    pub moves: u32,
+    pub lines: u32,
+    // figures which cannot be placed at the end
+    pub left: Vec<u8>,
+}
+
+pub trait Strategy {
+    fn name(&self) -> &'static str;
+    // next move or `None` if nothing can be placed
+    fn choose(&mut self, field: &Field, basket: &BasketSystem) -> Option<Placement>;
+    // forget everything about the last game
+    fn reset(&mut self) {}
+}
+
+// best move for the current field only
+pub struct Greedy {
+    large: Vec<Figure>,
+    // figures can be turned
+    rotation: bool,
+}
+
+// best moves for all orderings of the basket figures
+pub struct Lookahead {
+    large: Vec<Figure>,
+    rotation: bool,
+    plan: Vec<Placement>,
+}
+
+impl Greedy {
+    pub fn new(figures: &[Figure], rotation: bool) -> Greedy {
+        Greedy { large: hint::large_figures(figures), rotation }
+    }
+}
+
+impl Strategy for Greedy {
+    fn name(&self) -> &'static str {
+        "greedy"
+    }
+
+    fn choose(&mut self, field: &Field, basket: &BasketSystem) -> Option<Placement> {
+        let mut best: Option<(i32, Placement)> = None;
+        for (index, figure) in candidates(basket) {
+            for (rating, cell, turns, ..) in rated_moves(field, figure, Lines::empty(), &self.large, self.rotation) {
+                if best.is_none_or(|(value, _)| rating > value) {
+                    best = Some((rating, Placement { basket: index, cell, turns }));
+                }
+            }
+        }
+        best.map(|(_, placement)| placement)
+    }
+}
+
+impl Lookahead {
+    pub fn new(figures: &[Figure], rotation: bool) -> Lookahead {
+        Lookahead { large: hint::large_figures(figures), rotation, plan: Vec::new() }
+    }
+
+    // best rating and moves for the rest of the basket
+    fn search(&self, field: &Field, basket: &BasketSystem, lines: Lines) -> (i32, Vec<Placement>) {
+        let mut best: Option<(i32, Vec<Placement>)> = None;
+        for (index, figure) in candidates(basket) {
+            let mut moves = rated_moves(field, figure, lines, &self.large, self.rotation);
+            moves.sort_by_key(|(rating, ..)| std::cmp::Reverse(*rating));
+
+            let mut rest = basket.clone();
+            rest.pop(index);
+            for (_, cell, turns, field, lines) in moves.into_iter().take(LOOKAHEAD_BEAM) {
+                let (rating, mut plan) = self.search(&field, &rest, lines);
+                if best.as_ref().is_none_or(|(value, _)| rating > *value) {
+                    plan.insert(0, Placement { basket: index, cell, turns });
+                    best = Some((rating, plan));
+                }
+            }
+        }
+        best.unwrap_or_else(|| {
+            let dead = basket.figures().len() as i32 * DEAD_END_PENALTY;
+            (hint::rate(field, lines, &self.large) - dead, Vec::new())
+        })
+    }
+}
+
+impl Strategy for Lookahead {
+    fn name(&self) -> &'static str {
+        "lookahead"
+    }
+
+    fn choose(&mut self, field: &Field, basket: &BasketSystem) -> Option<Placement> {
+        // follow the plan while it fits
+        let valid =
+            self.plan.first().is_some_and(|p| p.figure(basket).is_some_and(|f| field.can_set_figure(p.cell, &f)));
+        if !valid {
+            self.plan = self.search(field, basket, Lines::empty()).1;
+        }
+        if self.plan.is_empty() {
+            return None;
+        }
+        Some(self.plan.remove(0))
+    }
+
+    fn reset(&mut self) {
+        self.plan.clear();
+    }
+}
+
+// basket figures and the held one, the hold slot is filled only with the hold rule
+fn candidates(basket: &BasketSystem) -> impl Iterator<Item = (usize, &Figure)> {
+    (0..=basket.len()).filter_map(|index| basket.get(index).map(|figure| (index, figure)))
+}
+
+// basket figure which fits nowhere goes to the empty hold slot, so the next refill comes sooner
+pub fn park(engine: &GameEngine) -> Option<usize> {
+    let rules = engine.rules();
+    if !rules.hold || engine.basket().hold().is_some() {
+        return None;
+    }
+    (0..engine.basket().len()).find(|&index| {
+        engine.basket().get(index).is_some_and(|figure| {
+            hint::orientations(figure, rules.rotation)
+                .iter()
+                .all(|(_, figure)| hint::cells(engine.field(), figure).next().is_none())
+        })
+    })
+}
+
+// all moves of the figure with rating, turns, new field and removed lines
+fn rated_moves(
+    field: &Field, figure: &Figure, lines: Lines, large: &[Figure], rotation: bool,
+) -> Vec<(i32, Coord, u8, Field, Lines)> {
+    let mut moves = Vec::new();
+    for (turns, figure) in hint::orientations(figure, rotation) {
+        for cell in hint::cells(field, &figure) {
+            let Some((next, removed)) = hint::apply(field, cell, &figure) else {
+                continue;
+            };
+            let lines = Lines { x: lines.x + removed.x, y: lines.y + removed.y };
+            moves.push((hint::rate(&next, lines, large), cell, turns, next, lines));
+        }
+    }
+    moves
+}
+
+pub fn strategy(name: &str, figures: &[Figure], rotation: bool) -> Option<Box<dyn Strategy>> {
+    match name {
+        "greedy" => Some(Box::new(Greedy::new(figures, rotation))),
+        "lookahead" => Some(Box::new(Lookahead::new(figures, rotation))),
+        _ => None,
+    }
+}
+
+// play the game until the end
+pub fn play(engine: &mut GameEngine, strategy: &mut dyn Strategy) -> GameStats {
+    let mut stats = GameStats::default();
+    while !engine.is_game_over() {
+        if let Some(index) = park(engine) {
+            if engine.hold(index).is_err() {
+                break;
+            }
+            continue;
+        }
+        let Some(Placement { basket, cell, turns }) = strategy.choose(engine.field(), engine.basket()) else {
+            break;
+        };
+        for _ in 0..turns {
+            engine.rotate(basket);
+        }
+        let Ok(events) = engine.place(basket, cell) else {
+            break;
+        };
+        for event in events {
+            if let Event::Cleared { lines, .. } = event {
+                stats.lines += lines.x + lines.y;
+            }
+        }
+        stats.moves += 1;
+    }
+    stats.score = engine.score();
+    stats.left = engine.basket().figures().iter().map(|figure| figure.index).collect();
+    stats
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::{HOLD_INDEX, Rules, default_figures};
+    use crate::figure;
+
+    #[test]
+    fn greedy_plays_full_game() {
+        let mut engine = GameEngine::new(default_figures(), 1010);
+        let mut strategy = strategy("greedy", engine.figures(), false).unwrap();
+        assert_eq!(strategy.name(), "greedy");
+        let stats = play(&mut engine, strategy.as_mut());
+        assert!(stats.moves > 0);
+        assert_eq!(stats.score, engine.score());
+        assert!(!stats.left.is_empty());
+        assert!(engine.is_game_over());
+    }
+
+    #[test]
+    fn lookahead_follows_plan() {
+        let mut engine = GameEngine::new(default_figures(), 42);
+        let mut strategy = strategy("lookahead", engine.figures(), false).unwrap();
+        // a few baskets are enough
+        for _ in 0..12 {
+            let Placement { basket, cell, .. } = strategy.choose(engine.field(), engine.basket()).unwrap();
+            assert!(engine.place(basket, cell).is_ok());
+        }
+    }
+
+    #[test]
+    fn park_and_place_held() {
+        let rules = Rules { hold: true, ..Rules::default() };
+        let mut engine = GameEngine::with_rules(default_figures(), 1010, rules);
+        // only single blocks fit into a checkerboard
+        for y in 0..10 {
+            for x in (y % 2..10).step_by(2) {
+                engine.field_mut().set(coord!(x, y), 0);
+            }
+        }
+        let (single, square) = (figure!(98, 0; (0, 0)), figure!(99, 0; (0, 0), (1, 0), (0, 1), (1, 1)));
+        engine.basket_mut().clear();
+        engine.basket_mut().set(0, single.clone());
+        engine.basket_mut().set(1, square);
+        assert_eq!(park(&engine), Some(1));
+        engine.hold(1).unwrap();
+        assert_eq!(park(&engine), None);
+
+        // held block is the only one which fits
+        engine.basket_mut().clear();
+        engine.basket_mut().set(0, figure!(97, 0; (0, 0), (1, 0)));
+        engine.basket_mut().set(HOLD_INDEX, single);
+        for name in STRATEGIES {
+            let placement = strategy(name, engine.figures(), false).unwrap().choose(engine.field(), engine.basket());
+            assert_eq!(placement.map(|placement| placement.basket), Some(HOLD_INDEX));
+        }
+    }
+
+    #[test]
+    fn hold_game() {
+        let rules = Rules { hold: true, ..Rules::default() };
+        let mut engine = GameEngine::with_rules(default_figures(), 1010, rules);
+        let mut strategy = strategy("greedy", engine.figures(), false).unwrap();
+        let stats = play(&mut engine, strategy.as_mut());
+        assert!(stats.moves > 0);
+        assert!(engine.is_game_over());
+    }
+}
@@ -0,0 +1,346 @@
+// headless batch of computer games, prints aggregate statistics
+use std::collections::BTreeMap;
+use std::env;
+use std::process;
+use std::str::FromStr;
+
+use rs_1010::ai::{self, GameStats};
+use rs_1010::board::BoardMask;
+use rs_1010::engine::{self, GameEngine, Rules};
+use rs_1010::figures::FigureSet;
+use rs_1010::policy::{POLICIES, Policy};
+use rs_1010::scoring::{BLOCK_COST_MULTIPLIER, LINE_MULTIPLIER, SCORINGS, Scoring};
+
+const DEFAULT_GAMES: u32 = 100;
+const DEFAULT_SEED: u32 = 1;
+const DEFAULT_STRATEGY: &str = "greedy";
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Format {
+    Text,
+    Csv,
+    Json,
+}
+
+struct Options {
+    strategy: String,
+    games: u32,
+    seed: u32,
+    format: Format,
+    figures: Option<String>,
+    mask: Option<String>,
+    rules: Rules,
+}
+
+// mean, median and max of the values
+struct Summary {
+    mean: f64,
+    median: f64,
+    max: u32,
+}
+
+struct Report {
+    strategy: String,
+    games: u32,
+    seed: u32,
+    set: String,
+    // board size or name of the mask
+    board: String,
+    rules: Rules,
+    figures: usize,
+    score: Summary,
+    moves: Summary,
+    lines: Summary,
+    // figure index -> games where it was left in the basket
+    game_over: BTreeMap<u8, u32>,
+}
+
+impl FromStr for Format {
+    type Err = ();
+
+    fn from_str(value: &str) -> Result<Format, ()> {
+        match value {
+            "text" => Ok(Format::Text),
+            "csv" => Ok(Format::Csv),
+            "json" => Ok(Format::Json),
+            _ => Err(()),
+        }
+    }
+}
+
+impl Options {
+    fn from_args() -> Options {
+        let mut options = Options {
+            strategy: DEFAULT_STRATEGY.to_string(),
+            games: DEFAULT_GAMES,
+            seed: DEFAULT_SEED,
+            format: Format::Text,
+            figures: None,
+            mask: None,
+            rules: Rules::default(),
+        };
+        let mut args = env::args().skip(1);
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--ai" => options.strategy = value(&arg, args.next()),
+                "--games" => options.games = value(&arg, args.next()),
+                "--seed" => options.seed = value(&arg, args.next()),
+                "--format" => options.format = value(&arg, args.next()),
+                "--figures" => options.figures = Some(value(&arg, args.next())),
+                "--mask" => options.mask = Some(value(&arg, args.next())),
+                "--random" => options.rules.policy = value(&arg, args.next()),
+                "--scoring" => options.rules.scoring = value(&arg, args.next()),
+                "--fair" => options.rules.fair = true,
+                "--rotation" => options.rules.rotation = true,
+                "--hold" => options.rules.hold = true,
+                "--board" => {
+                    let value: String = value(&arg, args.next());
+                    options.rules.board = engine::parse_board(&value).unwrap_or_else(|err| {
+                        eprintln!("[warning] {}", err);
+                        usage(1);
+                    });
+                }
+                "--help" => usage(0),
+                _ => {
+                    eprintln!("[warning] unknown argument `{}`", arg);
+                    usage(1);
+                }
+            }
+        }
+        options
+    }
+}
+
+impl Summary {
+    fn new(mut values: Vec<u32>) -> Summary {
+        if values.is_empty() {
+            return Summary { mean: 0.0, median: 0.0, max: 0 };
+        }
+        values.sort_unstable();
+        let len = values.len();
+        let mean = values.iter().map(|&v| v as f64).sum::<f64>() / len as f64;
+        let median = if len.is_multiple_of(2) {
+            (values[len / 2 - 1] as f64 + values[len / 2] as f64) / 2.0
+        } else {
+            values[len / 2] as f64
+        };
+        Summary { mean, median, max: values[len - 1] }
+    }
+
+    fn json(&self) -> String {
+        format!("{{\"mean\": {:.2}, \"median\": {:.1}, \"max\": {}}}", self.mean, self.median, self.max)
+    }
+}
+
+impl Report {
+    fn new(options: &Options, set: &FigureSet, board: &BoardMask, games: &[GameStats]) -> Report {
+        let mut game_over = BTreeMap::new();
+        for stats in games {
+            let mut left = stats.left.clone();
+            // count figure once per game
+            left.sort_unstable();
+            left.dedup();
+            for index in left {
+                *game_over.entry(index).or_insert(0) += 1;
+            }
+        }
+        Report {
+            strategy: options.strategy.clone(),
+            games: options.games,
+            seed: options.seed,
+            set: set.name.clone(),
+            board: if board.id() == 0 { format!("{}x{}", board.size.x, board.size.y) } else { board.name.clone() },
+            rules: options.rules,
+            // ids are not continuous in custom sets
+            figures: set.figures.iter().map(|f| f.index as usize).max().unwrap_or(0),
+            score: Summary::new(games.iter().map(|s| s.score).collect()),
+            moves: Summary::new(games.iter().map(|s| s.moves).collect()),
+            lines: Summary::new(games.iter().map(|s| s.lines).collect()),
+            game_over,
+        }
+    }
+
+    fn text(&self) -> String {
+        let mut result = format!(
+            "strategy: {}, games: {}, seeds: {}..{}\n",
+            self.strategy,
+            self.games,
+            self.seed,
+            self.seed as u64 + self.games as u64
+        );
+        result += &format!(
+            "figures: {}, board: {}, random: {}, scoring: {}, fair: {}, rotation: {}, hold: {}, line multiplier: {}, block cost: {}\n",
+            self.set,
+            self.board,
+            self.rules.policy,
+            self.rules.scoring,
+            self.rules.fair,
+            self.rules.rotation,
+            self.rules.hold,
+            LINE_MULTIPLIER,
+            BLOCK_COST_MULTIPLIER
+        );
+        for (name, summary) in [("score", &self.score), ("moves", &self.moves), ("lines", &self.lines)] {
+            result +=
+                &format!("{:<6} mean {:.2}, median {:.1}, max {}\n", name, summary.mean, summary.median, summary.max);
+        }
+        result += "game over figures:\n";
+        for (index, count) in &self.game_over {
+            result += &format!("  #{:<2} {}\n", index, count);
+        }
+        result
+    }
+
+    fn csv(&self) -> String {
+        let mut header = [
+            "strategy",
+            "games",
+            "seed",
+            "figures",
+            "board",
+            "random",
+            "scoring",
+            "fair",
+            "rotation",
+            "hold",
+            "line_multiplier",
+            "block_cost_multiplier",
+        ]
+        .into_iter()
+        .map(String::from)
+        .collect::<Vec<_>>();
+        let mut row = vec![
+            csv_field(&self.strategy),
+            self.games.to_string(),
+            self.seed.to_string(),
+            csv_field(&self.set),
+            csv_field(&self.board),
+            self.rules.policy.to_string(),
+            self.rules.scoring.to_string(),
+            self.rules.fair.to_string(),
+            self.rules.rotation.to_string(),
+            self.rules.hold.to_string(),
+            LINE_MULTIPLIER.to_string(),
+            BLOCK_COST_MULTIPLIER.to_string(),
+        ];
+        for (name, summary) in [("score", &self.score), ("moves", &self.moves), ("lines", &self.lines)] {
+            header.extend([format!("{}_mean", name), format!("{}_median", name), format!("{}_max", name)]);
+            row.extend([format!("{:.2}", summary.mean), format!("{:.1}", summary.median), summary.max.to_string()]);
+        }
+        // every figure has own column to compare runs
+        for index in 1..=self.figures as u8 {
+            header.push(format!("game_over_{}", index));
+            row.push(self.game_over.get(&index).copied().unwrap_or(0).to_string());
+        }
+        format!("{}\n{}\n", header.join(","), row.join(","))
+    }
+
+    fn json(&self) -> String {
+        let game_over =
+            self.game_over.iter().map(|(index, count)| format!("\"{}\": {}", index, count)).collect::<Vec<_>>();
+        format!(
+            "{{\"strategy\": {}, \"games\": {}, \"seed\": {}, \"figures\": {}, \"board\": {}, \"random\": \"{}\", \"scoring\": \"{}\", \"fair\": {}, \"rotation\": {}, \"hold\": {}, \"line_multiplier\": {}, \
+             \"block_cost_multiplier\": {}, \"score\": {},\"moves\": {}, \"lines\": {}, \"game_over\": {{{}}}}}\n",
+            json_string(&self.strategy),
+            self.games,
+            self.seed,
+            json_string(&self.set),
+            json_string(&self.board),
+            self.rules.policy,
+            self.rules.scoring,
+            self.rules.fair,
+            self.rules.rotation,
+            self.rules.hold,
+            LINE_MULTIPLIER,
+            BLOCK_COST_MULTIPLIER,
+            self.score.json(),
+            self.moves.json(),
+            self.lines.json(),
+            game_over.join(", ")
+        )
+    }
+}
+
+// names of the figure set and board come from files
+fn json_string(value: &str) -> String {
+    let mut result = String::from("\"");
+    for c in value.chars() {
+        match c {
+            '"' => result += "\\\"",
+            '\\' => result += "\\\\",
+            '\n' => result += "\\n",
+            '\r' => result += "\\r",
+            '\t' => result += "\\t",
+            c if c.is_control() => result += &format!("\\u{:04x}", c as u32),
+            c => result.push(c),
+        }
+    }
+    result + "\""
+}
+
+// RFC 4180: fields with separators, quotes or line breaks are quoted
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn value<T: FromStr>(arg: &str, value: Option<String>) -> T {
+    match value.map(|v| v.parse()) {
+        Some(Ok(value)) => value,
+        _ => {
+            eprintln!("[warning] bad or missing value for `{}`", arg);
+            usage(1);
+        }
+    }
+}
+
+fn usage(code: i32) -> ! {
+    eprintln!(
+        "usage: simulate [--ai {}] [--games N] [--seed FIRST] [--format text|csv|json] [--figures FILE] \
+         [--random {}] [--scoring {}] [--fair] [--rotation] [--hold] [--board WxH] [--mask FILE]",
+        ai::STRATEGIES.join("|"),
+        POLICIES.map(Policy::name).join("|"),
+        SCORINGS.map(Scoring::name).join("|")
+    );
+    process::exit(code);
+}
+
+fn main() {
+    let options = Options::from_args();
+    let set = match &options.figures {
+        Some(file) => FigureSet::load(file).unwrap_or_else(|err| {
+            eprintln!("[figures::warning] problem with load `{}`: {}", file, err);
+            process::exit(1);
+        }),
+        None => FigureSet::default(),
+    };
+    let board = match &options.mask {
+        Some(file) => BoardMask::load(file).unwrap_or_else(|err| {
+            eprintln!("[board::warning] problem with load `{}`: {}", file, err);
+            process::exit(1);
+        }),
+        None => BoardMask::new(options.rules.board),
+    };
+    let Some(mut strategy) = ai::strategy(&options.strategy, &set.figures, options.rules.rotation) else {
+        eprintln!("[ai::warning] unknown strategy `{}`", options.strategy);
+        usage(1);
+    };
+
+    let mut games = Vec::new();
+    for seed in (0..options.games).map(|game| options.seed.wrapping_add(game)) {
+        let mut engine = GameEngine::with_board(set.figures.clone(), seed, options.rules, &board);
+        strategy.reset();
+        games.push(ai::play(&mut engine, strategy.as_mut()));
+    }
+
+    let report = Report::new(&options, &set, &board, &games);
+    let result = match options.format {
+        Format::Text => report.text(),
+        Format::Csv => report.csv(),
+        Format::Json => report.json(),
+    };
+    print!("{}", result);
+}
@@ -0,0 +1,163 @@
+use std::ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, Not, Shl};
+
+const WORDS: usize = 4;
+const WORD_BITS: usize = u64::BITS as usize;
+pub const BITS: usize = WORDS * WORD_BITS;
+
+// fixed size bit set, the first word keeps the lowest bits
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Bits([u64; WORDS]);
+
+impl Bits {
+    pub fn empty() -> Bits {
+        Bits([0; WORDS])
+    }
+
+    pub fn bit(index: usize) -> Bits {
+        let mut bits = Bits::empty();
+        bits.insert(index);
+        bits
+    }
+
+    // first `count` bits are set
+    pub fn ones(count: usize) -> Bits {
+        let mut bits = Bits::empty();
+        for (index, word) in bits.0.iter_mut().enumerate() {
+            let rest = count.saturating_sub(index * WORD_BITS);
+            *word = if rest >= WORD_BITS { u64::MAX } else { (1 << rest) - 1 };
+        }
+        bits
+    }
+
+    pub fn insert(&mut self, index: usize) {
+        if index < BITS {
+            self.0[index / WORD_BITS] |= 1 << (index % WORD_BITS);
+        }
+    }
+
+    pub fn remove(&mut self, index: usize) {
+        if index < BITS {
+            self.0[index / WORD_BITS] &= !(1 << (index % WORD_BITS));
+        }
+    }
+
+    pub fn contains(&self, index: usize) -> bool {
+        index < BITS && self.0[index / WORD_BITS] & (1 << (index % WORD_BITS)) != 0
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.iter().all(|&word| word == 0)
+    }
+
+    pub fn count_ones(&self) -> u32 {
+        self.0.iter().map(|word| word.count_ones()).sum()
+    }
+
+    // indexes of the set bits from the lowest one
+    pub fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        self.0.iter().enumerate().flat_map(|(index, &word)| {
+            let mut rest = word;
+            std::iter::from_fn(move || {
+                if rest == 0 {
+                    return None;
+                }
+                let bit = rest.trailing_zeros() as usize;
+                rest &= rest - 1;
+                Some(index * WORD_BITS + bit)
+            })
+        })
+    }
+}
+
+impl From<u128> for Bits {
+    fn from(value: u128) -> Bits {
+        Bits([value as u64, (value >> WORD_BITS) as u64, 0, 0])
+    }
+}
+
+impl BitAnd for Bits {
+    type Output = Bits;
+
+    fn bitand(mut self, rhs: Bits) -> Bits {
+        self &= rhs;
+        self
+    }
+}
+
+impl BitAndAssign for Bits {
+    fn bitand_assign(&mut self, rhs: Bits) {
+        for (word, other) in self.0.iter_mut().zip(rhs.0) {
+            *word &= other;
+        }
+    }
+}
+
+impl BitOr for Bits {
+    type Output = Bits;
+
+    fn bitor(mut self, rhs: Bits) -> Bits {
+        self |= rhs;
+        self
+    }
+}
+
+impl BitOrAssign for Bits {
+    fn bitor_assign(&mut self, rhs: Bits) {
+        for (word, other) in self.0.iter_mut().zip(rhs.0) {
+            *word |= other;
+        }
+    }
+}
+
+impl Not for Bits {
+    type Output = Bits;
+
+    fn not(mut self) -> Bits {
+        for word in self.0.iter_mut() {
+            *word = !*word;
+        }
+        self
+    }
+}
+
+// bits shifted out of the set are lost
+impl Shl<usize> for Bits {
+    type Output = Bits;
+
+    fn shl(self, shift: usize) -> Bits {
+        let mut result = Bits::empty();
+        let (words, bits) = (shift / WORD_BITS, shift % WORD_BITS);
+        for index in (words..WORDS).rev() {
+            let mut word = self.0[index - words] << bits;
+            if bits > 0 && index > words {
+                word |= self.0[index - words - 1] >> (WORD_BITS - bits);
+            }
+            result.0[index] = word;
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shift_over_words() {
+        let bits = Bits::from(0b101 << 62) << 70;
+        assert_eq!(bits.iter().collect::<Vec<_>>(), vec![132, 134]);
+        assert_eq!(Bits::bit(200) << 56, Bits::empty());
+        assert_eq!(Bits::ones(3) << 64, Bits::bit(64) | Bits::bit(65) | Bits::bit(66));
+    }
+
+    #[test]
+    fn set_operations() {
+        let mut bits = Bits::ones(130);
+        assert_eq!(bits.count_ones(), 130);
+        bits.remove(129);
+        assert!(!bits.contains(129) && bits.contains(128));
+        assert_eq!((bits & Bits::bit(5)).iter().collect::<Vec<_>>(), vec![5]);
+        assert_eq!((!bits).count_ones(), BITS as u32 - 129);
+        assert!((bits & !bits).is_empty());
+    }
+}
@@ -0,0 +1,197 @@
+use std::fs;
+use std::path::Path;
+
+use crate::coord::Coord;
+use crate::engine::{self, Field};
+
+pub const DEFAULT_BOARD_NAME: &str = "plain";
+
+const COMMENT: char = ';';
+const FREE: char = '.';
+const BLOCKED: char = '#';
+const ABSENT: char = '-';
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Cell {
+    Free,
+    // cell is on the board, but figures cannot be set there
+    Blocked,
+    // cell is not the part of the board
+    Absent,
+}
+
+// shape of the game field
+//
+// file format:
+//   ; comment
+//   name <board name>
+//
+//   -..-
+//   .##.
+//
+// `.` is a playable cell, `#` is a blocked cell and `-` is out of the board
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BoardMask {
+    pub name: String,
+    pub size: Coord,
+    // cells are stored row by row
+    cells: Vec<Cell>,
+}
+
+impl BoardMask {
+    // rectangle board without blocked cells
+    pub fn new(size: Coord) -> BoardMask {
+        let cells = vec![Cell::Free; (size.x * size.y) as usize];
+        BoardMask { name: DEFAULT_BOARD_NAME.to_string(), size, cells }
+    }
+
+    pub fn parse(data: &str) -> Result<BoardMask, String> {
+        let mut name = None;
+        let mut rows: Vec<Vec<Cell>> = Vec::new();
+
+        for (number, text) in data.lines().enumerate() {
+            let line = number + 1;
+            let text = text.split(COMMENT).next().unwrap_or_default().trim();
+            let keyword = text.split_whitespace().next().unwrap_or_default();
+            match keyword {
+                "" => (),
+                "name" => name = Some(text[keyword.len()..].trim().to_string()),
+                _ if text.starts_with([FREE, BLOCKED, ABSENT]) => {
+                    let row = text.chars().map(|c| cell(c).ok_or(c)).collect::<Result<Vec<_>, _>>();
+                    rows.push(row.map_err(|c| format!("line {}: unknown cell symbol `{}`", line, c))?);
+                }
+                _ => return Err(format!("line {}: unknown keyword `{}`", line, keyword)),
+            }
+        }
+
+        let name = name.filter(|name| !name.is_empty()).unwrap_or_else(|| "custom".to_string());
+        BoardMask::from_rows(name, rows)
+    }
+
+    // board from the rows of cells
+    pub(crate) fn from_rows(name: String, rows: Vec<Vec<Cell>>) -> Result<BoardMask, String> {
+        if let Some(row) = rows.iter().find(|row| row.len() != rows[0].len()) {
+            return Err(format!("board rows have different length: {} and {}", rows[0].len(), row.len()));
+        }
+        let size = coord!(rows.first().map_or(0, Vec::len) as i16, rows.len() as i16);
+        if !engine::is_board_size(size) {
+            return Err(format!(
+                "board is {}x{}, but sides must be in {}..={}",
+                size.x,
+                size.y,
+                engine::FIELD_MIN_LEN,
+                engine::FIELD_MAX_LEN
+            ));
+        }
+        let cells: Vec<Cell> = rows.into_iter().flatten().collect();
+        if !cells.contains(&Cell::Free) {
+            return Err("board has no playable cells".to_string());
+        }
+        Ok(BoardMask { name, size, cells })
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<BoardMask, String> {
+        let data = fs::read_to_string(path).map_err(|e| e.to_string())?;
+        BoardMask::parse(&data)
+    }
+
+    pub fn get(&self, pos: &Coord) -> Option<Cell> {
+        if pos.x < 0 || pos.x >= self.size.x || pos.y < 0 || pos.y >= self.size.y {
+            return None;
+        }
+        Some(self.cells[(pos.y * self.size.x + pos.x) as usize])
+    }
+
+    // empty field of the board shape
+    pub fn field(&self) -> Field {
+        let mut field = Field::new(self.size);
+        for (index, cell) in self.cells.iter().enumerate() {
+            if *cell != Cell::Free {
+                let index = index as i16;
+                field.block(coord!(index % self.size.x, index / self.size.x));
+            }
+        }
+        field
+    }
+
+    // FNV-1a hash of the cells, 0 is a rectangle board
+    pub fn id(&self) -> u32 {
+        if self.cells.iter().all(|&cell| cell == Cell::Free) {
+            return 0;
+        }
+        let mut hash: u32 = 0x811c9dc5;
+        let bytes = [self.size.x as u8, self.size.y as u8].into_iter().chain(self.cells.iter().map(|&cell| cell as u8));
+        for byte in bytes {
+            hash ^= byte as u32;
+            hash = hash.wrapping_mul(0x01000193);
+        }
+        hash.max(1)
+    }
+}
+
+// cell of the board symbol
+pub(crate) fn cell(symbol: char) -> Option<Cell> {
+    match symbol {
+        FREE => Some(Cell::Free),
+        BLOCKED => Some(Cell::Blocked),
+        ABSENT => Some(Cell::Absent),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resource_boards() {
+        let boards = [
+            include_str!("../resources/boards/plus.txt"),
+            include_str!("../resources/boards/donut.txt"),
+            include_str!("../resources/boards/diamond.txt"),
+        ];
+        for data in boards {
+            let board = BoardMask::parse(data).unwrap();
+            assert_ne!(board.id(), 0, "{}", board.name);
+        }
+    }
+
+    #[test]
+    fn parse_board() {
+        let board = BoardMask::parse("name corner\n-....\n.....\n..#..\n.....\n.....").unwrap();
+        assert_eq!(board.name, "corner");
+        assert_eq!(board.size, coord!(5, 5));
+        assert_eq!(board.get(&coord!(0, 0)), Some(Cell::Absent));
+        assert_eq!(board.get(&coord!(2, 2)), Some(Cell::Blocked));
+        assert_eq!(board.get(&coord!(4, 4)), Some(Cell::Free));
+        assert_eq!(board.get(&coord!(5, 0)), None);
+
+        let field = board.field();
+        assert!(!field.is_free(&coord!(0, 0)) && !field.is_free(&coord!(2, 2)));
+        assert_eq!(field.free_cells(), 23);
+    }
+
+    #[test]
+    fn id_depends_on_cells() {
+        assert_eq!(BoardMask::new(coord!(8, 12)).id(), 0);
+        let one = BoardMask::parse(".....\n.....\n..#..\n.....\n.....").unwrap();
+        let other = BoardMask::parse(".....\n.....\n..-..\n.....\n.....").unwrap();
+        assert_ne!(one.id(), other.id());
+        assert_eq!(one.id(), BoardMask::parse("name x\n.....\n.....\n..#..\n.....\n.....").unwrap().id());
+    }
+
+    #[test]
+    fn validation() {
+        let errors = [
+            "....\n....\n....\n....",
+            ".....\n.....\n.....\n.....\n....",
+            ".....\n.....\n..x..\n.....\n.....",
+            "#####\n#####\n#####\n#####\n-----",
+            "size 5\n.....\n.....\n.....\n.....\n.....",
+            "",
+        ];
+        for data in errors {
+            assert!(BoardMask::parse(data).is_err(), "{:?}", data);
+        }
+    }
+}
@@ -0,0 +1,40 @@
+use std::env;
+use std::str::FromStr;
+
+// command line options
+#[derive(Default)]
+pub struct Options {
+    pub seed: Option<u32>,
+    pub replay: Option<String>,
+    pub speed: Option<f64>,
+    pub ai: Option<String>,
+    pub headless: bool,
+}
+
+impl Options {
+    pub fn from_args() -> Options {
+        let mut options = Options::default();
+        let mut args = env::args().skip(1);
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--seed" => options.seed = value(&arg, args.next()),
+                "--replay" => options.replay = value(&arg, args.next()),
+                "--speed" => options.speed = value(&arg, args.next()),
+                "--ai" => options.ai = value(&arg, args.next()),
+                "--headless" => options.headless = true,
+                _ => eprintln!("[warning] unknown argument `{}`", arg),
+            }
+        }
+        options
+    }
+}
+
+fn value<T: FromStr>(arg: &str, value: Option<String>) -> Option<T> {
+    match value.map(|v| v.parse()) {
+        Some(Ok(value)) => Some(value),
+        _ => {
+            eprintln!("[warning] bad or missing value for `{}`", arg);
+            None
+        }
+    }
+}
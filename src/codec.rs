@@ -1,3 +1,4 @@
+use std::fmt;
 use std::ops::{AddAssign, ShlAssign};
 
 const ALPHABET: &str = "abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789+~";
@@ -12,10 +13,22 @@ pub struct Encoder {
 
 pub struct Decoder {
     buffer: Vec<u8>,
+    // number of encoded bits, the last byte can be filled partially
+    size: usize,
     byte: usize,
     bit: usize,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CodecError {
+    // symbols out of the alphabet with their positions in the text
+    InvalidChars(Vec<(usize, char)>),
+    // more bits are requested than left in the data
+    Exhausted { requested: u8, remaining: usize },
+    // value is wider than 64 bits
+    TooWide(u8),
+}
+
 impl Encoder {
     pub fn new() -> Encoder {
         Encoder { encoded: String::new(), value: 0, index: 0 }
@@ -58,9 +71,47 @@ impl Encoder {
     }
 }
 
+// CRC-32 (IEEE) of the data
+pub fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0_u32;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..BYTE_SIZE {
+            crc = if crc & 1 == 1 { (crc >> 1) ^ 0xedb88320 } else { crc >> 1 };
+        }
+    }
+    !crc
+}
+
+impl fmt::Display for CodecError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CodecError::InvalidChars(chars) => {
+                let chars: Vec<_> = chars.iter().map(|(position, c)| format!("`{}` at {}", c, position)).collect();
+                write!(f, "invalid symbols {}", chars.join(", "))
+            }
+            CodecError::Exhausted { requested, remaining } => {
+                write!(f, "{} bits are requested, but only {} are left", requested, remaining)
+            }
+            CodecError::TooWide(size) => write!(f, "{} bits do not fit into 64 bit value", size),
+        }
+    }
+}
+
+impl Default for Encoder {
+    fn default() -> Self {
+        Encoder::new()
+    }
+}
+
 impl Decoder {
-    pub fn decode(data: &str) -> Option<Decoder> {
-        let index = |character: u8| ALPHABET.find(character as char).unwrap() as u8;
+    pub fn decode(data: &str) -> Result<Decoder, CodecError> {
+        let invalid: Vec<_> = data.chars().enumerate().filter(|(_, c)| !ALPHABET.contains(*c)).collect();
+        if !invalid.is_empty() {
+            return Err(CodecError::InvalidChars(invalid));
+        }
+        // every symbol is checked above
+        let index = |character: u8| ALPHABET.find(character as char).unwrap_or_default() as u8;
         let mut buffer = Vec::with_capacity(data.len() * BLOCK_SIZE / BYTE_SIZE);
 
         for block in data.as_bytes().chunks(4) {
@@ -84,7 +135,8 @@ impl Decoder {
 
                     buffer.push((a << 2) + (b >> 4));
                     buffer.push(((b & 0b1111) << 4) + (c >> 2));
-                    buffer.push(c & 0b11);
+                    // the first decoder dropped the shift and lost the last two bits
+                    buffer.push((c & 0b11) << 6);
                 }
                 // | aaaaaabb | bbbb.... |
                 [a, b] => {
@@ -100,7 +152,12 @@ impl Decoder {
             }
         }
 
-        Some(Decoder { buffer, byte: 0, bit: 0 })
+        Ok(Decoder { buffer, size: data.len() * BLOCK_SIZE, byte: 0, bit: 0 })
+    }
+
+    // bits which are not taken yet, padding of the last symbol included
+    pub fn remaining_bits(&self) -> usize {
+        self.size.saturating_sub(self.byte * BYTE_SIZE + self.bit)
     }
 
     fn take_aligned_bytes<T>(&mut self, mut current: T, count: usize) -> T
@@ -108,7 +165,10 @@ impl Decoder {
         T: Default + AddAssign<T> + ShlAssign<usize> + From<u8> + Copy,
     {
         for byte in self.buffer.iter().skip(self.byte).take(count) {
+            // a whole byte per step, the first decoder shifted by 7 bits and broke values over 255,
+            // two steps to avoid shift overflow for u8
             current <<= BYTE_SIZE - 1;
+            current <<= 1;
             current += T::from(*byte);
         }
 
@@ -148,14 +208,18 @@ impl Decoder {
         current
     }
 
-    pub fn take<T>(&mut self, size: u8) -> Option<T>
+    pub fn take<T>(&mut self, size: u8) -> Result<T, CodecError>
     where
         T: Default + AddAssign<T> + ShlAssign<usize> + From<u8> + Copy,
     {
-        let size = size as usize;
         if size > 64 {
-            return None;
+            return Err(CodecError::TooWide(size));
+        }
+        let remaining = self.remaining_bits();
+        if size as usize > remaining {
+            return Err(CodecError::Exhausted { requested: size, remaining });
         }
+        let size = size as usize;
 
         let mut copy_bytes = size / BYTE_SIZE;
         let mut copy_bits = size % BYTE_SIZE;
@@ -188,7 +252,7 @@ impl Decoder {
             result = self.take_bits(result, copy_bits);
         }
 
-        Some(result)
+        Ok(result)
     }
 
     #[allow(dead_code)]
@@ -201,6 +265,9 @@ impl Decoder {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::engine::{GameEngine, default_figures};
+    use crate::random::Random;
+    use crate::save::{self, Assists};
 
     #[test]
     fn serialize_6bits() {
@@ -253,19 +320,82 @@ mod tests {
     #[test]
     fn deserialize_6_set_bits() {
         let mut decoder = Decoder::decode("~").unwrap();
-        assert_eq!(decoder.take::<u8>(6), Some(0b111111));
+        assert_eq!(decoder.take::<u8>(6), Ok(0b111111));
     }
 
     #[test]
     fn deserialize_6_empty_bits() {
         let mut decoder = Decoder::decode("a").unwrap();
-        assert_eq!(decoder.take::<u8>(6), Some(0));
+        assert_eq!(decoder.take::<u8>(6), Ok(0));
     }
 
     #[test]
     fn deserialize_full_byte() {
         let mut decoder = Decoder::decode("kG").unwrap();
-        assert_eq!(decoder.take::<u8>(8), Some(42));
+        assert_eq!(decoder.take::<u8>(8), Ok(42));
+    }
+
+    #[test]
+    fn broken_data() {
+        assert_eq!(Decoder::decode("ab.cä").err(), Some(CodecError::InvalidChars(vec![(2, '.'), (4, 'ä')])));
+        let mut decoder = Decoder::decode("kG").unwrap();
+        assert_eq!(decoder.take::<u16>(10), Ok(42 << 2));
+        assert_eq!(decoder.remaining_bits(), 2);
+        assert_eq!(decoder.take::<u8>(8), Err(CodecError::Exhausted { requested: 8, remaining: 2 }));
+        assert_eq!(decoder.take::<u64>(65), Err(CodecError::TooWide(65)));
+        // failed take does not move the position
+        assert_eq!(decoder.take::<u8>(2), Ok(0));
+        assert_eq!(decoder.remaining_bits(), 0);
+    }
+
+    // random sequences of (value, width) pairs are decoded back
+    #[test]
+    fn random_round_trip() {
+        let mut rnd = Random::new(1010);
+        for _ in 0..500 {
+            let count = rnd.below(40);
+            let items: Vec<(u64, u8)> = (0..count)
+                .map(|_| {
+                    let width = rnd.below(64) as u8 + 1;
+                    let value = ((rnd.rand() as u64) << 32 | rnd.rand() as u64) >> (64 - width);
+                    (value, width)
+                })
+                .collect();
+
+            let mut encoder = Encoder::new();
+            for &(value, width) in &items {
+                encoder.push(value as i64, width).unwrap();
+            }
+            let mut decoder = Decoder::decode(&encoder.result()).unwrap();
+            for &(value, width) in &items {
+                assert_eq!(decoder.take::<u64>(width), Ok(value), "{:?}", items);
+            }
+            // only padding of the last symbol is left
+            assert!(decoder.remaining_bits() < 2 * BLOCK_SIZE, "{:?}", items);
+        }
+    }
+
+    // any text of the alphabet is decoded without panic
+    #[test]
+    fn random_text() {
+        let mut rnd = Random::new(42);
+        let alphabet: Vec<char> = ALPHABET.chars().collect();
+        for _ in 0..500 {
+            let text: String = (0..rnd.below(20)).map(|_| alphabet[rnd.below(64) as usize]).collect();
+            let mut decoder = Decoder::decode(&text).unwrap();
+            assert_eq!(decoder.remaining_bits(), text.len() * BLOCK_SIZE);
+            while decoder.remaining_bits() > 0 {
+                let width = (rnd.below(64) as usize + 1).min(decoder.remaining_bits()) as u8;
+                assert!(decoder.take::<u64>(width).is_ok());
+            }
+            assert!(decoder.take::<u8>(1).is_err());
+        }
+    }
+
+    #[test]
+    fn checksum() {
+        assert_eq!(crc32(b"123456789"), 0xcbf43926);
+        assert_eq!(crc32(b""), 0);
     }
 
     #[test]
@@ -334,6 +464,26 @@ mod tests {
         assert_eq!(result, expected);
     }
 
+    #[test]
+    fn multi_byte_values() {
+        let sizes = vec![8, 32, 3, 32, 64];
+        let expected = vec![255, 1010, 5, u32::MAX as i64, i64::MAX];
+
+        let mut encoder = Encoder::new();
+        for (value, size) in expected.iter().zip(&sizes) {
+            encoder.push(*value, *size);
+        }
+        let encoded = encoder.result();
+
+        let mut decoder = Decoder::decode(&encoded).unwrap();
+        let mut result = vec![];
+        for size in sizes {
+            result.push(decoder.take::<i64>(size).unwrap());
+        }
+
+        assert_eq!(result, expected);
+    }
+
     #[test]
     fn skip_bits() {
         let mut encoder = Encoder::new();
@@ -343,6 +493,38 @@ mod tests {
         let mut decoder = Decoder::decode(&encoded).unwrap();
         decoder.skip(4);
 
-        assert_eq!(decoder.take(4), Some(0b0101));
+        assert_eq!(decoder.take(4), Ok(0b0101));
+    }
+
+    #[test]
+    fn baseline_aligned_bytes() {
+        // 0x1234 in 16 bits and 0xdeadbeef in 32 bits by the first encoder
+        let mut decoder = Decoder::decode("eJtERB7V").unwrap();
+
+        assert_eq!(decoder.take::<u32>(16), Ok(0x1234));
+        assert_eq!(decoder.take::<u64>(32), Ok(0xdeadbeef));
+    }
+
+    #[test]
+    fn baseline_three_symbol_tail() {
+        // 5 in 6 bits and 0b101011 in 12 bits by the first encoder
+        let mut decoder = Decoder::decode("faR").unwrap();
+
+        assert_eq!(decoder.take::<u32>(6), Ok(5));
+        assert_eq!(decoder.take::<u32>(12), Ok(0b101011));
+        assert_eq!(decoder.remaining_bits(), 0);
+    }
+
+    // found by fuzzing: the game is loaded over the field with blocks
+    #[test]
+    fn fuzz_load_over_blocks() {
+        let mut engine = GameEngine::new(default_figures(), 1);
+        engine.field_mut().set(coord!(9, 0), 7);
+        let data = save::serialize(&engine, 95, Assists::default());
+
+        let mut played = engine.clone();
+        played.field_mut().set(coord!(0, 0), 3);
+        assert_eq!(save::deserialize(&data, &mut played, &mut Assists::default()), Ok(95));
+        assert_eq!(played.field().blocks(), engine.field().blocks());
     }
 }
@@ -1,9 +1,29 @@
 use tini::Ini;
 
+use rs_1010::engine::FIELD_LEN;
+
 // game strings
 pub const GAME_OVER_TEXT: &str = "your name: ";
 pub const GAME_OVER: &str = "GAME OVER";
 pub const GAME_PAUSE: &str = "PAUSED";
+pub const LEVEL_SELECT: &str = "LEVELS";
+pub const LEVEL_SOLVED: &str = "SOLVED";
+pub const LEVEL_FAILED: &str = "FAILED";
+pub const LEVEL_DONE_MARK: &str = "done";
+pub const ENDLESS_GAME: &str = "endless game";
+pub const MODE_SELECT: &str = "MODES";
+pub const PUZZLE_MODE: &str = "puzzle";
+pub const DAILY_STREAK: &str = "streak";
+pub const DAILY_DONE: &str = "done";
+pub const SLOT_SELECT: &str = "SAVES";
+pub const SLOT_SAVE: &str = "save current game";
+pub const SLOT_BACK: &str = "back";
+pub const SLOT_NAME_TEXT: &str = "slot name: ";
+// default slot name prefix, autosave slot has the mode name
+pub const SLOT_NAME: &str = "slot";
+pub const AUTOSAVE_NAME: &str = "autosave";
+pub const SLOT_OVER_MARK: &str = "over";
+pub const PRACTICE_GAME: &str = "practice: pasted position";
 pub const GT: &str = "1010";
 
 // errors
@@ -15,17 +35,15 @@ pub const GET_COLOR_ERROR: &str = "Cannot get color";
 // resource & config
 pub const FONT_FILE: &str = "./resources/FiraMono-Regular.ttf";
 pub const CONFIG_FILE: &str = "./resources/config.ini";
+pub const REPLAY_DIR: &str = "./replays";
+pub const SAVE_DIR: &str = "./saves";
+pub const DEFAULT_PUZZLE_FILE: &str = "./resources/levels.txt";
 pub const GAMESCORE_COUNT: usize = 5;
 
 // game fps param
 pub const MILLISECOND: u32 = 1000;
 
-// game score multiplier
-pub const LINE_MULTIPLIER: u32 = 30;
-pub const BLOCK_COST_MULTIPLIER: u32 = 5;
-
 // filed params
-pub const FIELD_LEN: u8 = 10;
 pub const FIELD_SHIFT_WIDTH: i16 = 210;
 pub const FIELD_SHIFT_HEIGHT: i16 = 60;
 pub const FIELD_BASKET_SEP: u32 = 10;
@@ -37,19 +55,23 @@ pub const TILE_SIZE_1: u8 = 64;
 pub const TILE_SEP_1: u8 = 4;
 
 // basket params
-pub const BASKET_COUNT: u8 = 3;
 pub const BASKET_SIZE: u8 = 5;
 pub const BASKET_SHIFT: u8 = 7;
 pub const BASKET_ROUND_STEPS: i16 = 8;
 // basket tile size & separator
 pub const TILE_SIZE_2: u8 = 32;
 pub const TILE_SEP_2: u8 = 2;
-
-// gameover round rect radius
-pub const FIELD_WIDTH: u32 =
-    FIELD_SHIFT_WIDTH as u32 + (TILE_SIZE_1 as u32 + TILE_SEP_1 as u32) * FIELD_LEN as u32 + FIELD_BASKET_SEP;
+// preview tile size & separator
+pub const TILE_SIZE_3: u8 = 8;
+pub const TILE_SEP_3: u8 = 1;
+pub const PREVIEW_SEP: u32 = 10;
+pub const PREVIEW_ROUND_RADIUS: i16 = 2;
+
+// space for the field, tiles of large boards are smaller
+pub const FIELD_AREA: u32 = (TILE_SIZE_1 as u32 + TILE_SEP_1 as u32) * FIELD_LEN as u32;
 pub const BASKET_WIDTH: u32 = (TILE_SIZE_2 as u32 + TILE_SEP_2 as u32) * BASKET_SIZE as u32;
 pub const BASKET_HEIGHT: u32 = BASKET_WIDTH + BASKET_SHIFT as u32;
+pub const PREVIEW_WIDTH: u32 = (TILE_SIZE_3 as u32 + TILE_SEP_3 as u32) * BASKET_SIZE as u32 + PREVIEW_SEP;
 
 // game window size
 pub const W_WIDTH: u32 = 1280;
@@ -67,28 +89,48 @@ pub const DEFAULT_USER_NAME: &str = "user";
 pub const DEFAULT_MAGNET_PARAM: bool = true;
 pub const DEFAULT_BLEND: bool = true;
 pub const DEFAULT_ALPHA_PARAM: u8 = 150;
+pub const DEFAULT_HINT_ALPHA: u8 = 80;
 pub const DEFAULT_FPS_PARAM: u32 = 60;
 pub const DEFAULT_SHOW_FPS: bool = false;
+pub const DEFAULT_SAVE_REPLAY: bool = true;
+pub const DEFAULT_REPLAY_SPEED: f64 = 1.0;
+pub const MIN_REPLAY_SPEED: f64 = 0.25;
+pub const MAX_REPLAY_SPEED: f64 = 16.0;
+// computer player delay between actions in milliseconds
+pub const AI_MOVE_DELAY: f64 = 300.0;
+pub const DEFAULT_UNDO: bool = true;
+pub const DEFAULT_MARK_UNDO: bool = true;
+pub const DEFAULT_POLICY: &str = "uniform";
+pub const DEFAULT_SCORING: &str = "classic";
+pub const DEFAULT_FAIR: bool = false;
+pub const DEFAULT_ROTATION: bool = false;
+pub const DEFAULT_HOLD: bool = false;
+pub const DEFAULT_SHOW_PREVIEW: bool = true;
+pub const DEFAULT_BOARD: &str = "10x10";
+// seconds for one move in the blitz mode
+pub const DEFAULT_BLITZ_TIME: u32 = 10;
 
 // other
 pub const MAX_NAME_SIZE: usize = 14;
 pub const BORDER: i16 = 6;
 pub const MINIMAL_TILE_SIZE: u8 = 4;
-pub const TILE_CLEAN_ANIMATION_SIZE: u8 = (TILE_SIZE_1 / 2) - MINIMAL_TILE_SIZE;
-
-// serde bits
-pub const SERDE_FIELD_SIZE: u8 = 1;
-pub const SERDE_FIGURE_SIZE: u8 = 5;
-pub const SERDE_SCORE_SIZE: u8 = 32;
-pub const SERDE_TIME_SIZE: u8 = 64;
-pub const SERDE_PADDING_SIZE: u8 = 5;
-pub const SERDE_V2_SUPPORT: u8 = 0b10101;
-pub const SERDE_COLOR: u8 = 8;
-pub const SERDE_POS: u8 = 4;
+// frames of the score label over the cleared lines
+pub const LABEL_FRAMES: u8 = 45;
+
+// score marks
+pub const UNDO_MARK: char = '*';
+pub const HINT_MARK: char = 'h';
+pub const FAIR_MARK: char = 'f';
+pub const ROTATION_MARK: char = 'r';
+pub const HOLD_MARK: char = '+';
+pub const BOARD_MARK: char = '#';
+pub const MASK_MARK: char = '@';
+pub const SCORING_MARK: char = '$';
 
 // default game colors
 pub const GAME_BACKGROUND_COLOR: &[u8; 3] = &[110, 110, 110];
 pub const FIELD_BACKGROUND_COLOR: &[u8; 3] = &[170, 170, 170];
+pub const BLOCKED_COLOR: &[u8; 3] = &[80, 80, 80];
 pub const FONT_ACOLOR: &[u8; 3] = &[200, 200, 200];
 pub const FONT_BCOLOR: &[u8; 3] = &[255, 255, 255];
 pub const BORDER_COLOR: &[u8; 3] = &[210, 210, 210];
@@ -131,16 +173,44 @@ pub const DEFAULT_MUSIC_ENABLE: bool = true;
 pub fn default_config() -> Ini {
     Ini::new()
         .section("score")
-        .items(vec![("users", ""), ("scores", ""), ("times", "")])
+        .items(vec![
+            ("users", ""),
+            ("scores", ""),
+            ("times", ""),
+            ("undo", ""),
+            ("hints", ""),
+            ("fair", ""),
+            ("rotation", ""),
+            ("hold", ""),
+            ("board", ""),
+            ("mask", ""),
+            ("mode", ""),
+            ("scoring", ""),
+        ])
+        .section("daily")
+        .items(vec![("dates", ""), ("scores", "")])
         .section("game")
         .item("show_highscore_at_start", DEFAULT_HIGHSCORE_AT_START)
         .item("magnetization", DEFAULT_MAGNET_PARAM)
         .item("blend", DEFAULT_BLEND)
         .item("alpha", DEFAULT_ALPHA_PARAM)
+        .item("hint_alpha", DEFAULT_HINT_ALPHA)
         .item("fps", DEFAULT_FPS_PARAM)
         .item("show_fps", DEFAULT_SHOW_FPS)
         .item("username", DEFAULT_USER_NAME)
         .item("ask_username", true)
+        .item("save_replay", DEFAULT_SAVE_REPLAY)
+        .item("undo", DEFAULT_UNDO)
+        .item("mark_undo", DEFAULT_MARK_UNDO)
+        .item("random", DEFAULT_POLICY)
+        .item("scoring", DEFAULT_SCORING)
+        .item("fair", DEFAULT_FAIR)
+        .item("rotation", DEFAULT_ROTATION)
+        .item("hold", DEFAULT_HOLD)
+        .item("show_preview", DEFAULT_SHOW_PREVIEW)
+        .item("board", DEFAULT_BOARD)
+        .item("puzzle", DEFAULT_PUZZLE_FILE)
+        .item("blitz_time", DEFAULT_BLITZ_TIME)
         .section("audio")
         .item("enable_sfx", DEFAULT_SFX_ENABLE)
         .item("volume_sfx", DEFAULT_SFX_VOLUME)
@@ -149,6 +219,7 @@ pub fn default_config() -> Ini {
         .section("color")
         .item_vec("game_background", GAME_BACKGROUND_COLOR)
         .item_vec("field_background", FIELD_BACKGROUND_COLOR)
+        .item_vec("blocked", BLOCKED_COLOR)
         .item_vec("font", FONT_ACOLOR)
         .item_vec("light", FONT_BCOLOR)
         .item_vec("border", BORDER_COLOR)
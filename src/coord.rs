@@ -0,0 +1,136 @@
+use std::ops::{Add, Mul, Shr, Sub};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Coord {
+    pub x: i16,
+    pub y: i16,
+}
+
+#[macro_export]
+macro_rules! coord {
+    ($x:expr, $y:expr) => {
+        $crate::coord::Coord { x: $x, y: $y }
+    };
+    ($xy:expr) => {
+        $crate::coord!($xy, $xy)
+    };
+    () => {
+        $crate::coord!(0, 0)
+    };
+}
+
+#[macro_export]
+macro_rules! normalize {
+    ($param:expr; $lower:expr, $upper:expr) => {
+        if $param < $lower {
+            $param = $lower;
+        }
+        if $param > $upper {
+            $param = $upper;
+        }
+    };
+}
+
+impl Coord {
+    pub fn floor_frac(self, rhs: Coord) -> Self {
+        let xi = (self.x as f32 / rhs.x as f32).floor() as i16;
+        let yi = (self.y as f32 / rhs.y as f32).floor() as i16;
+        coord!(xi, yi)
+    }
+
+    pub fn normalize(mut self, lower: Coord, upper: Coord) -> Self {
+        normalize!(self.x; lower.x, upper.x);
+        normalize!(self.y; lower.y, upper.y);
+        self
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.x == 0 && self.y == 0
+    }
+}
+
+impl Add for Coord {
+    type Output = Self;
+
+    fn add(mut self, rhs: Self::Output) -> Self::Output {
+        self.x += rhs.x;
+        self.y += rhs.y;
+        self
+    }
+}
+
+impl<T> Add<T> for Coord
+where
+    T: Into<i16>,
+{
+    type Output = Self;
+
+    fn add(mut self, k: T) -> Self::Output {
+        let v = k.into();
+        self.x += v;
+        self.y += v;
+        self
+    }
+}
+
+impl Sub for Coord {
+    type Output = Self;
+
+    fn sub(mut self, rhs: Self::Output) -> Self::Output {
+        self.x -= rhs.x;
+        self.y -= rhs.y;
+        self
+    }
+}
+
+impl<T> Sub<T> for Coord
+where
+    T: Into<i16>,
+{
+    type Output = Self;
+
+    fn sub(mut self, k: T) -> Self::Output {
+        let v = k.into();
+        self.x -= v;
+        self.y -= v;
+        self
+    }
+}
+
+impl Mul for Coord {
+    type Output = Self;
+
+    fn mul(mut self, k: Self) -> Self::Output {
+        self.x *= k.x;
+        self.y *= k.y;
+        self
+    }
+}
+
+impl<T> Mul<T> for Coord
+where
+    T: Into<i16>,
+{
+    type Output = Self;
+
+    fn mul(mut self, k: T) -> Self::Output {
+        let v = k.into();
+        self.x *= v;
+        self.y *= v;
+        self
+    }
+}
+
+impl<T> Shr<T> for Coord
+where
+    T: Into<i16>,
+{
+    type Output = Self;
+
+    fn shr(mut self, k: T) -> Self::Output {
+        let v = k.into();
+        self.x >>= v;
+        self.y >>= v;
+        self
+    }
+}
@@ -0,0 +1,144 @@
+use std::fmt;
+use std::str::FromStr;
+use std::time::SystemTime;
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+// calendar day in UTC, so the daily challenge is the same for everyone
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Date {
+    pub year: i32,
+    pub month: u32,
+    pub day: u32,
+}
+
+impl Date {
+    // days since 1970-01-01, see http://howardhinnant.github.io/date_algorithms.html
+    pub fn from_days(days: i64) -> Date {
+        let z = days + 719468;
+        let era = z.div_euclid(146097);
+        let doe = z.rem_euclid(146097);
+        let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+        let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+        let mp = (5 * doy + 2) / 153;
+        let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+        let year = (yoe + era * 400 + (month <= 2) as i64) as i32;
+        Date { year, month, day }
+    }
+
+    pub fn days(&self) -> i64 {
+        let year = self.year as i64 - (self.month <= 2) as i64;
+        let era = year.div_euclid(400);
+        let yoe = year.rem_euclid(400);
+        let mp = (self.month as i64 + 9) % 12;
+        let doy = (153 * mp + 2) / 5 + self.day as i64 - 1;
+        let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+        era * 146097 + doe - 719468
+    }
+
+    // clock of this computer, no network is needed
+    pub fn today() -> Date {
+        let seconds = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).map_or(0, |time| time.as_secs());
+        Date::from_days((seconds / SECONDS_PER_DAY) as i64)
+    }
+
+    pub fn next(&self) -> Date {
+        Date::from_days(self.days() + 1)
+    }
+
+    pub fn prev(&self) -> Date {
+        Date::from_days(self.days() - 1)
+    }
+
+    // FNV-1a hash of the date text
+    pub fn seed(&self) -> u32 {
+        let mut hash: u32 = 0x811c9dc5;
+        for byte in self.to_string().bytes() {
+            hash ^= byte as u32;
+            hash = hash.wrapping_mul(0x01000193);
+        }
+        hash
+    }
+}
+
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+// `<year>-<month>-<day>`
+impl FromStr for Date {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Date, String> {
+        let error = || format!("bad date `{}`", value);
+        let mut parts = value.trim().splitn(3, '-');
+        let mut number = || parts.next().and_then(|part| part.parse::<u32>().ok()).ok_or_else(error);
+        let (year, month, day) = (number()?, number()?, number()?);
+        let date = Date { year: year as i32, month, day };
+        // days out of the month are moved to the next one
+        if !(1..=12).contains(&month) || day == 0 || Date::from_days(date.days()) != date {
+            return Err(error());
+        }
+        Ok(date)
+    }
+}
+
+// days in a row with a game, ends today or yesterday
+pub fn streak(dates: &[Date], today: Date) -> u32 {
+    let mut day = if dates.contains(&today) { today } else { today.prev() };
+    let mut count = 0;
+    while dates.contains(&day) {
+        count += 1;
+        day = day.prev();
+    }
+    count
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn calendar() {
+        let date = |year, month, day| Date { year, month, day };
+        let known =
+            [(0, date(1970, 1, 1)), (11016, date(2000, 2, 29)), (19782, date(2024, 2, 29)), (-1, date(1969, 12, 31))];
+        for (days, date) in known {
+            assert_eq!(Date::from_days(days), date);
+            assert_eq!(date.days(), days);
+        }
+        assert_eq!(date(2023, 12, 31).next(), date(2024, 1, 1));
+        assert_eq!(date(2023, 3, 1).prev(), date(2023, 2, 28));
+    }
+
+    #[test]
+    fn parse_date() {
+        let date: Date = "2024-02-29".parse().unwrap();
+        assert_eq!(date.to_string(), "2024-02-29");
+        for value in ["2023-02-29", "2024-13-01", "2024-00-10", "2024-01-00", "2024-01", "today", "2024-01-1x"] {
+            assert!(value.parse::<Date>().is_err(), "{}", value);
+        }
+    }
+
+    #[test]
+    fn seed_of_day() {
+        let today: Date = "2026-10-17".parse().unwrap();
+        assert_eq!(today.seed(), "2026-10-17".parse::<Date>().unwrap().seed());
+        assert_ne!(today.seed(), today.next().seed());
+    }
+
+    #[test]
+    fn streak_of_days() {
+        let today: Date = "2026-03-02".parse().unwrap();
+        let dates: Vec<Date> =
+            ["2026-02-26", "2026-02-28", "2026-03-01"].iter().map(|date| date.parse().unwrap()).collect();
+        // today is not played yet
+        assert_eq!(streak(&dates, today), 2);
+        assert_eq!(streak(&[today], today), 1);
+        assert_eq!(streak(&dates, today.next()), 0);
+        assert_eq!(streak(&[], today), 0);
+    }
+}
@@ -0,0 +1,1610 @@
+use crate::bits::{self, Bits};
+use crate::board::BoardMask;
+use crate::coord::Coord;
+use crate::policy::Policy;
+use crate::random::Random;
+use crate::scoring::{Points, Scoring};
+
+// field params
+pub const FIELD_LEN: u8 = 10;
+// every figure fits into the smallest field
+pub const FIELD_MIN_LEN: u8 = FIGURE_LEN;
+// cell position is stored in 4 bits
+pub const FIELD_MAX_LEN: u8 = 16;
+// bitboard holds up to 256 cells
+pub const FIELD_MAX_CELLS: usize = bits::BITS;
+
+// figure params
+pub const FIGURE_LEN: u8 = 5;
+// figure colors in the game palette
+pub const FIGURE_COLORS: u8 = 8;
+
+// basket params
+pub const BASKET_COUNT: u8 = 3;
+// hold slot goes after the baskets
+pub const HOLD_INDEX: usize = BASKET_COUNT as usize;
+// fair mode: dealt triples and placements checked for each one
+pub const FAIR_DEALS: u32 = 32;
+pub const FAIR_SEARCH_BUDGET: u32 = 2000;
+
+#[macro_export]
+macro_rules! figure {
+    ($i:expr, $c:expr; $( ($x:expr, $y:expr) ),*) => {
+        {
+            let slice = vec![$( $crate::coord!($x, $y), )* ];
+            $crate::engine::Figure::from_slice($i, &slice, $c)
+        }
+    };
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Lines {
+    pub x: u32,
+    pub y: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Block {
+    pub pos: Coord,
+    pub color: u8,
+}
+
+// figure blocks are packed row by row with `FIGURE_LEN` stride
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Figure {
+    mask: u128,
+    size: Coord,
+    color: u8,
+    // spawn weight
+    weight: u32,
+    // stable figure id in the figure set
+    pub index: u8,
+}
+
+// field blocks are packed row by row with `field_size.x` stride
+#[derive(Debug, Clone)]
+pub struct Field {
+    pub field_size: Coord,
+    field: Bits,
+    // cells which are not playable
+    blocked: Bits,
+    colors: [u8; FIELD_MAX_CELLS],
+    // masks of the first row and column
+    row: Bits,
+    column: Bits,
+}
+
+#[derive(Debug, Clone)]
+pub struct BasketSystem {
+    basket: Vec<Option<Figure>>,
+    rnd: Random,
+    policy: Policy,
+    // figure ids left in the bag
+    bag: Vec<u8>,
+    // reserve figure, index is `len()`
+    hold: Option<Figure>,
+    // pre-rolled figures of the next refill
+    next: Vec<Figure>,
+    // figure ids which are dealt instead of random ones
+    sequence: Option<Vec<u8>>,
+}
+
+// optional game rules
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rules {
+    pub policy: Policy,
+    // only triples which can be placed are dealt
+    pub fair: bool,
+    // basket figures can be turned, figure set is reduced to canonical shapes
+    pub rotation: bool,
+    // one figure can be parked in the hold slot
+    pub hold: bool,
+    // field width and height
+    pub board: Coord,
+    // id of the board mask, 0 is a rectangle board
+    pub mask: u32,
+    pub scoring: Scoring,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Event {
+    // figure from the basket is placed on the field
+    Placed { basket: usize, figure: u8, blocks: u32 },
+    // full lines are removed from the field
+    Cleared { lines: Lines, blocks: Vec<Block> },
+    // score of the move with the clear
+    Scored { points: Points },
+    // figure from the basket is swapped with the hold slot
+    Held { basket: usize },
+    // all baskets are empty and filled again
+    Refilled,
+    // no one figure from the basket can be placed
+    GameOver,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlaceError {
+    // basket index is out of range or basket is empty
+    EmptyBasket,
+    // figure is out of the field or intersects with other blocks
+    CannotPlace,
+    // game is already finished
+    GameOver,
+    // hold slot is disabled by the rules
+    NoHold,
+}
+
+#[derive(Debug, Clone)]
+pub struct GameEngine {
+    field: Field,
+    basket: BasketSystem,
+    figures: Vec<Figure>,
+    score: u32,
+    // moves with a clear in a row
+    streak: u32,
+    seed: u32,
+    // policy is kept by the basket
+    rules: Rules,
+    // figures for the basket
+    pool: Vec<Figure>,
+}
+
+impl Default for Rules {
+    fn default() -> Rules {
+        let board = coord!(FIELD_LEN as i16);
+        Rules {
+            policy: Policy::default(),
+            fair: false,
+            rotation: false,
+            hold: false,
+            board,
+            mask: 0,
+            scoring: Scoring::default(),
+        }
+    }
+}
+
+impl Rules {
+    // rules without policy and board packed into bits
+    pub fn flags(self) -> u8 {
+        self.fair as u8 | (self.rotation as u8) << 1 | (self.hold as u8) << 2 | self.scoring.id() << 3
+    }
+
+    // unknown scoring or bits are reported, the game is not played by other rules
+    pub fn with_flags(mut self, bits: u8) -> Option<Rules> {
+        if bits >> 5 != 0 {
+            return None;
+        }
+        self.fair = bits & 1 == 1;
+        self.rotation = bits & 2 == 2;
+        self.hold = bits & 4 == 4;
+        self.scoring = Scoring::from_id(bits >> 3 & 3)?;
+        Some(self)
+    }
+}
+
+impl Lines {
+    pub fn empty() -> Lines {
+        Lines { x: 0, y: 0 }
+    }
+
+    pub fn not_empty(self) -> bool {
+        self.x != 0 || self.y != 0
+    }
+}
+
+impl Figure {
+    pub fn from_slice(index: u8, coords: &[Coord], color: u8) -> Figure {
+        let mut mask = 0;
+        let mut size = coord!();
+        for p in coords {
+            assert!((0..FIGURE_LEN as i16).contains(&p.x) && (0..FIGURE_LEN as i16).contains(&p.y));
+            mask |= 1 << (p.y * FIGURE_LEN as i16 + p.x);
+            size = coord!(size.x.max(p.x), size.y.max(p.y));
+        }
+        Figure { mask, size, color, weight: 1, index }
+    }
+
+    pub fn with_weight(mut self, weight: u32) -> Figure {
+        self.weight = weight;
+        self
+    }
+
+    pub fn blocks(&self) -> u32 {
+        self.mask.count_ones()
+    }
+
+    pub fn color(&self) -> u8 {
+        self.color
+    }
+
+    pub fn weight(&self) -> u32 {
+        self.weight
+    }
+
+    // figure turned 90 degrees clockwise
+    pub fn rotate(&self) -> Figure {
+        let coords: Vec<_> = self.iter().map(|p| coord!(self.size.y - p.y, p.x)).collect();
+        self.reshape(&coords)
+    }
+
+    // figure flipped from left to right
+    pub fn mirror(&self) -> Figure {
+        let coords: Vec<_> = self.iter().map(|p| coord!(self.size.x - p.x, p.y)).collect();
+        self.reshape(&coords)
+    }
+
+    fn reshape(&self, coords: &[Coord]) -> Figure {
+        Figure::from_slice(self.index, coords, self.color).with_weight(self.weight)
+    }
+
+    // all different orientations, the figure itself is the first
+    pub fn rotations(&self) -> Vec<Figure> {
+        let mut rotations = vec![self.clone()];
+        let mut figure = self.rotate();
+        while figure.mask != self.mask {
+            let next = figure.rotate();
+            rotations.push(figure);
+            figure = next;
+        }
+        rotations
+    }
+
+    // clockwise turns from `base` to this figure
+    pub fn turns_from(&self, base: &Figure) -> Option<u8> {
+        base.rotations().iter().position(|figure| figure.mask == self.mask).map(|turns| turns as u8)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = Coord> + '_ {
+        let len = FIGURE_LEN as i16;
+        (0..len * len).filter(|i| self.mask & (1 << i) != 0).map(move |i| coord!(i % len, i / len))
+    }
+
+    pub fn max(&self) -> Coord {
+        self.size
+    }
+
+    // repack figure mask with field stride
+    fn mask(&self, stride: i16) -> Bits {
+        let row = (1 << FIGURE_LEN) - 1;
+        let mut mask = 0;
+        for y in 0..=self.size.y {
+            mask |= ((self.mask >> (y * FIGURE_LEN as i16)) & row) << (y * stride);
+        }
+        Bits::from(mask)
+    }
+}
+
+impl Field {
+    pub fn new(size: Coord) -> Field {
+        assert!(is_board_size(size));
+        let (width, height) = (size.x as usize, size.y as usize);
+
+        let row = Bits::ones(width);
+        let column = (0..height).fold(Bits::empty(), |mask, y| mask | Bits::bit(y * width));
+        let empty = Bits::empty();
+        Field { field_size: size, field: empty, blocked: empty, colors: [0; FIELD_MAX_CELLS], row, column }
+    }
+
+    // cell cannot be used anymore
+    pub fn block(&mut self, pos: Coord) {
+        if let Some(bit) = self.bit(&pos) {
+            self.field.remove(bit);
+            self.blocked.insert(bit);
+        }
+    }
+
+    pub fn is_blocked(&self, pos: &Coord) -> bool {
+        self.bit(pos).is_some_and(|bit| self.blocked.contains(bit))
+    }
+
+    pub fn init_square(pole_size: u8) -> Field {
+        Field::new(coord!(pole_size as i16))
+    }
+
+    fn bit(&self, pos: &Coord) -> Option<usize> {
+        if pos.x < 0 || pos.x >= self.field_size.x || pos.y < 0 || pos.y >= self.field_size.y {
+            return None;
+        }
+        Some((pos.y * self.field_size.x + pos.x) as usize)
+    }
+
+    pub fn set(&mut self, pos: Coord, color: u8) {
+        if let Some(bit) = self.bit(&pos).filter(|&bit| !self.blocked.contains(bit)) {
+            self.field.insert(bit);
+            self.colors[bit] = color;
+        }
+    }
+
+    pub fn unset(&mut self, pos: &Coord) {
+        if let Some(bit) = self.bit(pos) {
+            self.field.remove(bit);
+        }
+    }
+
+    pub fn is_set(&self, pos: &Coord) -> bool {
+        self.bit(pos).is_some_and(|bit| self.field.contains(bit))
+    }
+
+    // cell is inside the field and empty
+    pub fn is_free(&self, pos: &Coord) -> bool {
+        self.bit(pos).is_some_and(|bit| !self.field.contains(bit) && !self.blocked.contains(bit))
+    }
+
+    pub fn get_color(&self, pos: &Coord) -> Option<u8> {
+        self.bit(pos).filter(|&bit| self.field.contains(bit)).map(|bit| self.colors[bit])
+    }
+
+    // figure mask shifted to `pos` or `None` if figure is out of the field
+    fn figure_mask(&self, pos: Coord, figure: &Figure) -> Option<Bits> {
+        let last = pos + figure.max();
+        if pos.x < 0 || pos.y < 0 || last.x >= self.field_size.x || last.y >= self.field_size.y {
+            return None;
+        }
+        Some(figure.mask(self.field_size.x) << (pos.y * self.field_size.x + pos.x) as usize)
+    }
+
+    pub fn can_set_figure(&self, pos: Coord, figure: &Figure) -> bool {
+        self.figure_mask(pos, figure).is_some_and(|mask| ((self.field | self.blocked) & mask).is_empty())
+    }
+
+    pub fn set_figure(&mut self, pos: Coord, figure: &Figure) -> bool {
+        if !self.can_set_figure(pos, figure) {
+            return false;
+        }
+        for p in figure.iter() {
+            self.set(p + pos, figure.color);
+        }
+        true
+    }
+
+    // playable cells of the row
+    fn line_h(&self, index: u8) -> Bits {
+        (self.row << (index as i16 * self.field_size.x) as usize) & !self.blocked
+    }
+
+    // playable cells of the column
+    fn line_v(&self, index: u8) -> Bits {
+        (self.column << index as usize) & !self.blocked
+    }
+
+    // rows are indexed by y and columns by x
+    fn check_line_h(&self, index: u8) -> Option<bool> {
+        if index as i16 >= self.field_size.y {
+            return None;
+        }
+        let line = self.line_h(index);
+        // line without playable cells is never full
+        Some(!line.is_empty() && self.field & line == line)
+    }
+
+    fn check_line_v(&self, index: u8) -> Option<bool> {
+        if index as i16 >= self.field_size.x {
+            return None;
+        }
+        let line = self.line_v(index);
+        Some(!line.is_empty() && self.field & line == line)
+    }
+
+    pub fn can_set(&self, figures: &[Figure]) -> bool {
+        if figures.is_empty() {
+            return true;
+        }
+        let stride = self.field_size.x;
+        let used = self.field | self.blocked;
+        for figure in figures {
+            let mask = figure.mask(stride);
+            let Coord { x: x_max, y: y_max } = figure.max();
+            for y in 0..self.field_size.y - y_max {
+                for x in 0..self.field_size.x - x_max {
+                    // can set a figure?
+                    if (used & (mask << (y * stride + x) as usize)).is_empty() {
+                        return true;
+                    }
+                }
+            }
+        }
+        false
+    }
+
+    // some order of the figures can be placed, `budget` limits checked placements
+    pub fn can_place_all(&self, figures: &[Figure], budget: &mut u32) -> bool {
+        if figures.is_empty() {
+            return true;
+        }
+        for (item, figure) in figures.iter().enumerate() {
+            // the same figure gives the same orders
+            if figures[..item].contains(figure) {
+                continue;
+            }
+            let mut rest = figures.to_vec();
+            rest.remove(item);
+            let Coord { x: x_max, y: y_max } = figure.max();
+            for y in 0..self.field_size.y - y_max {
+                for x in 0..self.field_size.x - x_max {
+                    if *budget == 0 {
+                        return false;
+                    }
+                    let mut next = self.clone();
+                    if !next.set_figure(coord!(x, y), figure) {
+                        continue;
+                    }
+                    *budget -= 1;
+                    next.next_state();
+                    if next.can_place_all(&rest, budget) {
+                        return true;
+                    }
+                }
+            }
+        }
+        false
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.field.is_empty()
+    }
+
+    pub fn free_cells(&self) -> u32 {
+        (self.field_size.x * self.field_size.y) as u32 - (self.field | self.blocked).count_ones()
+    }
+
+    fn mask_blocks(&self, mask: Bits) -> Vec<Block> {
+        let width = self.field_size.x as usize;
+        mask.iter()
+            .map(|bit| Block { pos: coord!((bit % width) as i16, (bit / width) as i16), color: self.colors[bit] })
+            .collect()
+    }
+
+    pub fn blocks(&self) -> Vec<Block> {
+        self.mask_blocks(self.field)
+    }
+
+    pub fn clear(&mut self) -> Vec<Block> {
+        let blocks = self.blocks();
+        self.field = Bits::empty();
+        blocks
+    }
+
+    // remove all full lines and return removed blocks
+    pub fn next_state(&mut self) -> Option<(Lines, Vec<Block>)> {
+        let mut lines = Lines::empty();
+        let mut clear = Bits::empty();
+
+        // calc x lines
+        for x in 0..self.field_size.x {
+            if let Some(true) = self.check_line_v(x as u8) {
+                clear |= self.line_v(x as u8);
+                lines.x += 1;
+            }
+        }
+        // and y
+        for y in 0..self.field_size.y {
+            if let Some(true) = self.check_line_h(y as u8) {
+                clear |= self.line_h(y as u8);
+                lines.y += 1;
+            }
+        }
+
+        if !lines.not_empty() {
+            return None;
+        }
+
+        let blocks = self.mask_blocks(clear);
+        self.field &= !clear;
+        Some((lines, blocks))
+    }
+}
+
+impl BasketSystem {
+    pub fn new(count: u8, seed: u32, policy: Policy) -> BasketSystem {
+        let basket = vec![None; count as usize];
+        let (bag, next) = (Vec::new(), Vec::new());
+        BasketSystem { basket, rnd: Random::new(seed), policy, bag, hold: None, next, sequence: None }
+    }
+
+    pub fn len(&self) -> usize {
+        self.basket.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.basket.iter().all(Option::is_none)
+    }
+
+    // basket or hold slot
+    fn slot(&mut self, index: usize) -> Option<&mut Option<Figure>> {
+        if index == self.basket.len() { Some(&mut self.hold) } else { self.basket.get_mut(index) }
+    }
+
+    pub fn get(&self, index: usize) -> Option<&Figure> {
+        if index == self.basket.len() {
+            return self.hold.as_ref();
+        }
+        self.basket.get(index).and_then(Option::as_ref)
+    }
+
+    pub fn set(&mut self, index: usize, figure: Figure) {
+        if let Some(slot) = self.slot(index) {
+            *slot = Some(figure);
+        }
+    }
+
+    pub fn pop(&mut self, index: usize) -> Option<Figure> {
+        self.slot(index).and_then(Option::take)
+    }
+
+    pub fn hold(&self) -> Option<&Figure> {
+        self.hold.as_ref()
+    }
+
+    // swap basket figure with the hold slot
+    pub fn swap_hold(&mut self, index: usize) -> bool {
+        match self.basket.get_mut(index) {
+            Some(figure @ Some(_)) => {
+                std::mem::swap(figure, &mut self.hold);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub fn clear(&mut self) {
+        for fig in self.basket.iter_mut() {
+            *fig = None;
+        }
+        self.hold = None;
+    }
+
+    pub fn random_state(&self) -> u32 {
+        self.rnd.state()
+    }
+
+    pub fn set_random_state(&mut self, state: u32) {
+        self.rnd = Random::new(state);
+    }
+
+    pub fn policy(&self) -> Policy {
+        self.policy
+    }
+
+    pub fn bag(&self) -> &[u8] {
+        &self.bag
+    }
+
+    // continue saved figure sequence
+    pub fn restore_policy(&mut self, policy: Policy, bag: Vec<u8>) {
+        self.policy = policy;
+        self.bag = bag;
+    }
+
+    // position of the next figure in `figures`
+    fn pick(&mut self, figures: &[Figure], picked: &[usize]) -> usize {
+        let size = figures.len();
+        match self.policy {
+            Policy::Uniform => self.rnd.below(size as u32) as usize,
+            Policy::Weighted => {
+                let total: u32 = figures.iter().map(Figure::weight).sum();
+                if total == 0 {
+                    return self.rnd.below(size as u32) as usize;
+                }
+                let mut value = self.rnd.below(total);
+                for (item, figure) in figures.iter().enumerate() {
+                    if value < figure.weight() {
+                        return item;
+                    }
+                    value -= figure.weight();
+                }
+                size - 1
+            }
+            Policy::Bag => {
+                if self.bag.is_empty() {
+                    for figure in figures {
+                        self.bag.extend((0..figure.weight()).map(|_| figure.index));
+                    }
+                    // Fisher-Yates shuffle
+                    for i in (1..self.bag.len()).rev() {
+                        let j = self.rnd.below(i as u32 + 1) as usize;
+                        self.bag.swap(i, j);
+                    }
+                }
+                let id = self.bag.pop();
+                match figures.iter().position(|figure| Some(figure.index) == id) {
+                    Some(item) => item,
+                    None => self.rnd.below(size as u32) as usize,
+                }
+            }
+            Policy::NoRepeat => {
+                let free: Vec<_> = (0..size).filter(|item| !picked.contains(item)).collect();
+                if free.is_empty() {
+                    return self.rnd.below(size as u32) as usize;
+                }
+                free[self.rnd.below(free.len() as u32) as usize]
+            }
+        }
+    }
+
+    // figures for all baskets
+    fn roll(&mut self, figures: &[Figure]) -> Vec<Figure> {
+        if let Some(sequence) = self.sequence.as_mut() {
+            let count = self.basket.len().min(sequence.len());
+            let ids: Vec<_> = sequence.drain(..count).collect();
+            return ids
+                .into_iter()
+                .filter_map(|id| figures.iter().find(|figure| figure.index == id).cloned())
+                .collect();
+        }
+        let mut picked = Vec::with_capacity(self.basket.len());
+        for _ in 0..self.basket.len() {
+            let item = self.pick(figures, &picked);
+            picked.push(item);
+        }
+        picked.into_iter().map(|item| figures[item].clone()).collect()
+    }
+
+    // the next refill is rolled before it is needed, so the sequence is the same
+    pub fn preroll(&mut self, figures: &[Figure]) {
+        if self.next.is_empty() {
+            self.next = self.roll(figures);
+        }
+    }
+
+    pub fn next(&self) -> &[Figure] {
+        &self.next
+    }
+
+    // figures in the fixed order, nothing is dealt after the last one
+    pub fn set_sequence(&mut self, ids: Vec<u8>) {
+        self.sequence = Some(ids);
+        self.next.clear();
+    }
+
+    // figure ids of the fixed order which are not dealt yet
+    pub fn sequence(&self) -> Option<&[u8]> {
+        self.sequence.as_deref()
+    }
+
+    // continue saved preview, empty one is rolled on the next refill
+    pub fn set_next(&mut self, figures: Vec<Figure>) {
+        self.next = figures;
+    }
+
+    pub fn rnd_fill(&mut self, figures: &[Figure]) {
+        self.preroll(figures);
+        let next = self.roll(figures);
+        self.promote(next);
+    }
+
+    // shown preview goes to the basket as is, `next` is shown instead
+    fn promote(&mut self, next: Vec<Figure>) {
+        for (index, figure) in std::mem::replace(&mut self.next, next).into_iter().enumerate() {
+            self.set(index, figure);
+        }
+    }
+
+    pub fn check_and_refill(&mut self, figures: &[Figure]) -> bool {
+        if !self.is_empty() {
+            return false;
+        }
+        self.rnd_fill(figures);
+        true
+    }
+
+    pub fn figures(&self) -> Vec<Figure> {
+        self.basket.iter().flatten().cloned().collect()
+    }
+}
+
+impl GameEngine {
+    pub fn new(figures: Vec<Figure>, seed: u32) -> GameEngine {
+        GameEngine::with_rules(figures, seed, Rules::default())
+    }
+
+    // game on a rectangle board
+    pub fn with_rules(figures: Vec<Figure>, seed: u32, rules: Rules) -> GameEngine {
+        GameEngine::with_board(figures, seed, rules, &BoardMask::new(rules.board))
+    }
+
+    // board size and mask of the rules are taken from `board`
+    pub fn with_board(figures: Vec<Figure>, seed: u32, rules: Rules, board: &BoardMask) -> GameEngine {
+        let rules = Rules { board: board.size, mask: board.id(), ..rules };
+        let basket = BasketSystem::new(BASKET_COUNT, seed, rules.policy);
+        let field = board.field();
+        let pool = if rules.rotation { canonical_figures(&figures) } else { figures.clone() };
+        let mut engine = GameEngine { field, basket, figures, score: 0, streak: 0, seed, rules, pool };
+        engine.deal();
+        engine
+    }
+
+    pub fn seed(&self) -> u32 {
+        self.seed
+    }
+
+    pub fn random_state(&self) -> u32 {
+        self.basket.random_state()
+    }
+
+    // continue game from saved random sequence
+    pub fn restore_random(&mut self, seed: u32, state: u32) {
+        self.seed = seed;
+        self.basket.set_random_state(state);
+        self.basket.set_next(Vec::new());
+    }
+
+    // roll the preview if it is not restored
+    pub fn preroll(&mut self) {
+        if self.basket.next().is_empty() {
+            let next = self.fair_roll();
+            self.basket.set_next(next);
+        }
+    }
+
+    pub fn policy(&self) -> Policy {
+        self.basket.policy()
+    }
+
+    pub fn rules(&self) -> Rules {
+        Rules { policy: self.basket.policy(), ..self.rules }
+    }
+
+    // change rules of the current game, bag and board are kept
+    pub fn set_rules(&mut self, rules: Rules) {
+        let bag = self.basket.bag().to_vec();
+        self.basket.restore_policy(rules.policy, bag);
+        self.pool = if rules.rotation { canonical_figures(&self.figures) } else { self.figures.clone() };
+        self.rules = Rules { board: self.rules.board, mask: self.rules.mask, ..rules };
+    }
+
+    pub fn field(&self) -> &Field {
+        &self.field
+    }
+
+    pub fn field_mut(&mut self) -> &mut Field {
+        &mut self.field
+    }
+
+    pub fn basket(&self) -> &BasketSystem {
+        &self.basket
+    }
+
+    pub fn basket_mut(&mut self) -> &mut BasketSystem {
+        &mut self.basket
+    }
+
+    pub fn figures(&self) -> &[Figure] {
+        &self.figures
+    }
+
+    // figure by the stable id
+    pub fn figure(&self, index: u8) -> Option<&Figure> {
+        self.figures.iter().find(|figure| figure.index == index)
+    }
+
+    pub fn score(&self) -> u32 {
+        self.score
+    }
+
+    pub fn set_score(&mut self, score: u32) {
+        self.score = score;
+    }
+
+    pub fn streak(&self) -> u32 {
+        self.streak
+    }
+
+    pub fn set_streak(&mut self, streak: u32) {
+        self.streak = streak;
+    }
+
+    pub fn is_game_over(&self) -> bool {
+        let mut figures = self.basket.figures();
+        figures.extend(self.basket.hold().cloned());
+        if self.rules.rotation {
+            figures = figures.iter().flat_map(Figure::rotations).collect();
+        }
+        !self.field.can_set(&figures)
+    }
+
+    // park basket figure or swap it with the held one, only with hold rule
+    pub fn hold(&mut self, index: usize) -> Result<Vec<Event>, PlaceError> {
+        if !self.rules.hold {
+            return Err(PlaceError::NoHold);
+        }
+        if self.is_game_over() {
+            return Err(PlaceError::GameOver);
+        }
+        if !self.basket.swap_hold(index) {
+            return Err(PlaceError::EmptyBasket);
+        }
+
+        let mut events = vec![Event::Held { basket: index }];
+        if self.basket.is_empty() {
+            self.deal();
+            events.push(Event::Refilled);
+        }
+        if self.is_game_over() {
+            events.push(Event::GameOver);
+        }
+        Ok(events)
+    }
+
+    // turn basket figure clockwise, only with rotation rule
+    pub fn rotate(&mut self, index: usize) -> bool {
+        let Some(figure) = self.basket.get(index).filter(|_| self.rules.rotation) else {
+            return false;
+        };
+        let figure = figure.rotate();
+        self.basket.set(index, figure);
+        true
+    }
+
+    // set figure from basket `index` with the top left corner at `cell`
+    pub fn place(&mut self, index: usize, cell: Coord) -> Result<Vec<Event>, PlaceError> {
+        if self.is_game_over() {
+            return Err(PlaceError::GameOver);
+        }
+        let figure = self.basket.get(index).ok_or(PlaceError::EmptyBasket)?;
+        if !self.field.set_figure(cell, figure) {
+            return Err(PlaceError::CannotPlace);
+        }
+
+        let mut events = Vec::new();
+        let figure = self.basket.pop(index).ok_or(PlaceError::EmptyBasket)?;
+        events.push(Event::Placed { basket: index, figure: figure.index, blocks: figure.blocks() });
+
+        let cleared = self.field.next_state();
+        self.streak = if cleared.is_some() { self.streak.saturating_add(1) } else { 0 };
+        let lines = cleared.as_ref().map_or(Lines::empty(), |(lines, _)| *lines);
+        let points = self.rules.scoring.points(figure.blocks(), lines, self.streak, self.field.is_empty());
+        // score of the loaded game can be anything
+        self.score = self.score.saturating_add(points.total());
+        if let Some((lines, blocks)) = cleared {
+            events.push(Event::Cleared { lines, blocks });
+            events.push(Event::Scored { points });
+        }
+
+        if self.basket.is_empty() {
+            self.deal();
+            events.push(Event::Refilled);
+        }
+
+        if self.is_game_over() {
+            events.push(Event::GameOver);
+        }
+
+        Ok(events)
+    }
+
+    // throw basket figures away and deal the next ones, held figure is kept
+    pub fn skip(&mut self) -> Vec<Event> {
+        for index in 0..self.basket.len() {
+            self.basket.pop(index);
+        }
+        self.deal();
+        let mut events = vec![Event::Refilled];
+        if self.is_game_over() {
+            events.push(Event::GameOver);
+        }
+        events
+    }
+
+    // deal figures in the fixed order instead of random ones
+    pub fn set_sequence(&mut self, ids: Vec<u8>) {
+        self.basket.clear();
+        self.basket.set_sequence(ids);
+        self.deal();
+    }
+
+    // no figures left to place
+    pub fn is_out_of_figures(&self) -> bool {
+        self.basket.is_empty() && self.basket.hold().is_none()
+    }
+
+    // remove everything from the field and basket, score is kept
+    pub fn clear(&mut self) -> Vec<Block> {
+        self.basket.clear();
+        self.basket.set_next(Vec::new());
+        self.field.clear()
+    }
+
+    // start a new game, the same seed gives the same figure sequence
+    pub fn restart(&mut self, seed: u32) {
+        self.score = 0;
+        self.streak = 0;
+        self.seed = seed;
+        self.field.clear();
+        self.basket = BasketSystem::new(BASKET_COUNT, seed, self.basket.policy());
+        self.deal();
+    }
+
+    // fill the basket with the shown preview and roll the next one, fair mode is kept over the preview:
+    // the preview which does not fit anymore is replaced right before it is dealt
+    fn deal(&mut self) {
+        self.preroll();
+        // preview was checked on the field of the roll, the field has changed since then
+        if self.is_fair_mode() && !self.is_fair(self.basket.next()) {
+            let figures = self.fair_roll();
+            self.basket.set_next(figures);
+        }
+        let next = self.fair_roll();
+        self.basket.promote(next);
+    }
+
+    // fixed order is not rolled again
+    fn is_fair_mode(&self) -> bool {
+        self.rules.fair && self.basket.sequence().is_none()
+    }
+
+    // some order of the figures fits into the current field, the held figure can replace one of them
+    fn is_fair(&self, figures: &[Figure]) -> bool {
+        let mut budget = FAIR_SEARCH_BUDGET;
+        if self.field.can_place_all(figures, &mut budget) {
+            return true;
+        }
+        let Some(held) = self.basket.hold() else {
+            return false;
+        };
+        (0..figures.len()).any(|item| {
+            let mut figures = figures.to_vec();
+            figures[item] = held.clone();
+            let mut budget = FAIR_SEARCH_BUDGET;
+            self.field.can_place_all(&figures, &mut budget)
+        })
+    }
+
+    // fair mode skips triples which cannot be placed on the field of the roll,
+    // the preview is rolled again on the deal only if it does not fit anymore
+    fn fair_roll(&mut self) -> Vec<Figure> {
+        let mut figures = self.basket.roll(&self.pool);
+        if !self.is_fair_mode() {
+            return figures;
+        }
+        for _ in 1..FAIR_DEALS {
+            if self.is_fair(&figures) {
+                break;
+            }
+            figures = self.basket.roll(&self.pool);
+        }
+        figures
+    }
+}
+
+// field fits into the bitboard and every figure fits into the field
+pub fn is_board_size(size: Coord) -> bool {
+    let range = FIELD_MIN_LEN as i16..=FIELD_MAX_LEN as i16;
+    range.contains(&size.x) && range.contains(&size.y) && (size.x * size.y) as usize <= FIELD_MAX_CELLS
+}
+
+// board size as `<width>x<height>` or `<len>` for square boards
+pub fn parse_board(value: &str) -> Result<Coord, String> {
+    let number = |text: &str| text.trim().parse::<i16>().map_err(|_| format!("bad board size `{}`", value));
+    let size = match value.split_once('x') {
+        Some((width, height)) => coord!(number(width)?, number(height)?),
+        None => coord!(number(value)?),
+    };
+    if !is_board_size(size) {
+        return Err(format!("board size must be in {}..={}", FIELD_MIN_LEN, FIELD_MAX_LEN));
+    }
+    Ok(size)
+}
+
+// figures without rotated copies of the previous ones
+pub fn canonical_figures(figures: &[Figure]) -> Vec<Figure> {
+    let mut canonical: Vec<Figure> = Vec::new();
+    for figure in figures {
+        if !canonical.iter().any(|base| figure.turns_from(base).is_some()) {
+            canonical.push(figure.clone());
+        }
+    }
+    canonical
+}
+
+// available game figures
+pub fn default_figures() -> Vec<Figure> {
+    vec![
+        // ###
+        // ###
+        // ###
+        figure!(1, 0; (0, 0), (1, 0), (2, 0), (0, 1), (1, 1), (2, 1), (0, 2), (1, 2), (2, 2)),
+        // ##
+        // ##
+        figure!(2, 0; (0, 0), (1, 0), (0, 1), (1, 1)),
+        // #
+        figure!(3, 0; (0, 0)),
+        // #####
+        figure!(4, 1; (0, 0), (0, 1), (0, 2), (0, 3), (0, 4)),
+        // #
+        // #
+        // #
+        // #
+        // #
+        figure!(5, 1; (0, 0), (1, 0), (2, 0), (3, 0), (4, 0)),
+        // #
+        // #
+        // #
+        // #
+        figure!(6, 2; (0, 0), (0, 1), (0, 2), (0, 3)),
+        // ####
+        figure!(7, 2; (0, 0), (1, 0), (2, 0), (3, 0)),
+        // #
+        // #
+        // #
+        figure!(8, 3; (0, 0), (0, 1), (0, 2)),
+        // ###
+        figure!(9, 3; (0, 0), (1, 0), (2, 0)),
+        // #
+        // #
+        figure!(10, 4; (0, 0), (0, 1)),
+        // ##
+        figure!(11, 4; (0, 0), (1, 0)),
+        // ###
+        //   #
+        //   #
+        figure!(12, 5; (0, 0), (1, 0), (2, 0), (2, 1), (2, 2)),
+        //   #
+        //   #
+        // ###
+        figure!(13, 5; (2, 0), (2, 1), (0, 2), (1, 2), (2, 2)),
+        // #
+        // #
+        // ###
+        figure!(14, 5; (0, 0), (0, 1), (0, 2), (1, 2), (2, 2)),
+        // ###
+        // #
+        // #
+        figure!(15, 5; (0, 0), (1, 0), (2, 0), (0, 1), (0, 2)),
+        // ###
+        //   #
+        figure!(16, 6; (0, 0), (1, 0), (2, 0), (2, 1)),
+        //   #
+        // ###
+        figure!(17, 6; (1, 0), (1, 1), (0, 2), (1, 2)),
+        // #
+        // ###
+        figure!(18, 6; (0, 0), (0, 1), (1, 1), (2, 1)),
+        // ##
+        // #
+        // #
+        figure!(19, 6; (0, 0), (1, 0), (0, 1), (0, 2)),
+        // ##
+        //  #
+        figure!(20, 7; (0, 0), (1, 0), (1, 1)),
+        //  #
+        // ##
+        figure!(21, 7; (1, 0), (0, 1), (1, 1)),
+        // #
+        // ##
+        figure!(22, 7; (0, 0), (0, 1), (1, 1)),
+        // ##
+        // #
+        figure!(23, 7; (0, 0), (1, 0), (0, 1)),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scoring::{BLOCK_COST_MULTIPLIER, LINE_MULTIPLIER};
+
+    fn fill_row(field: &mut Field, y: i16, skip: i16) {
+        for x in 0..field.field_size.x {
+            if x != skip {
+                field.set(coord!(x, y), 0);
+            }
+        }
+    }
+
+    #[test]
+    fn place_single_block() {
+        let mut engine = GameEngine::new(vec![figure!(1, 0; (0, 0))], 42);
+        let events = engine.place(0, coord!(3, 4)).unwrap();
+
+        assert!(engine.field().is_set(&coord!(3, 4)));
+        assert_eq!(engine.score(), BLOCK_COST_MULTIPLIER);
+        assert_eq!(events, vec![Event::Placed { basket: 0, figure: 1, blocks: 1 }]);
+        assert_eq!(engine.place(0, coord!(0, 0)), Err(PlaceError::EmptyBasket));
+        assert_eq!(engine.place(1, coord!(3, 4)), Err(PlaceError::CannotPlace));
+        assert_eq!(engine.place(1, coord!(10, 0)), Err(PlaceError::CannotPlace));
+    }
+
+    #[test]
+    fn clear_line() {
+        let mut engine = GameEngine::new(vec![figure!(1, 3; (0, 0))], 42);
+        fill_row(engine.field_mut(), 9, 5);
+
+        let events = engine.place(0, coord!(5, 9)).unwrap();
+        let lines = Lines { x: 0, y: 1 };
+        assert!(engine.field().is_empty());
+        assert_eq!(engine.score(), BLOCK_COST_MULTIPLIER + LINE_MULTIPLIER);
+        match &events[1] {
+            Event::Cleared { lines: cleared, blocks } => {
+                assert_eq!(*cleared, lines);
+                assert_eq!(blocks.len(), FIELD_LEN as usize);
+            }
+            _ => panic!("expected cleared event"),
+        }
+    }
+
+    // only single blocks fit into a checkerboard
+    fn checkerboard(field: &mut Field) {
+        for y in 0..field.field_size.y {
+            for x in (y % 2..field.field_size.x).step_by(2) {
+                field.set(coord!(x, y), 0);
+            }
+        }
+    }
+
+    #[test]
+    fn clear_streak() {
+        let rules = Rules { scoring: Scoring::Combo, ..Rules::default() };
+        let mut engine = GameEngine::with_rules(vec![figure!(1, 3; (0, 0))], 42, rules);
+        fill_row(engine.field_mut(), 9, 5);
+        let events = engine.place(0, coord!(5, 9)).unwrap();
+        let points =
+            Points { blocks: BLOCK_COST_MULTIPLIER, lines: LINE_MULTIPLIER, streak: 1, combo: 0, perfect: 300 };
+        assert_eq!(events[2], Event::Scored { points });
+        assert_eq!(engine.score(), points.total());
+
+        // second clear in a row doubles the line score
+        fill_row(engine.field_mut(), 0, 0);
+        let events = engine.place(1, coord!(0, 0)).unwrap();
+        assert!(
+            events.iter().any(|event| matches!(event, Event::Scored { points } if points.combo == LINE_MULTIPLIER))
+        );
+        assert_eq!(engine.streak(), 2);
+        engine.place(2, coord!(3, 3)).unwrap();
+        assert_eq!(engine.streak(), 0);
+    }
+
+    // found by fuzzing: score and streak of the saved game are not checked
+    #[test]
+    fn saturated_score() {
+        let rules = Rules { scoring: Scoring::Combo, ..Rules::default() };
+        let mut engine = GameEngine::with_rules(vec![figure!(1, 3; (0, 0))], 42, rules);
+        engine.set_score(u32::MAX);
+        engine.set_streak(u32::MAX);
+        fill_row(engine.field_mut(), 9, 5);
+        engine.place(0, coord!(5, 9)).unwrap();
+        assert_eq!((engine.score(), engine.streak()), (u32::MAX, u32::MAX));
+    }
+
+    #[test]
+    fn rule_flags() {
+        let rules = Rules { fair: true, hold: true, scoring: Scoring::Combo, ..Rules::default() };
+        assert_eq!(Rules::default().with_flags(rules.flags()), Some(rules));
+        // scoring 3 is not defined
+        assert_eq!(Rules::default().with_flags(3 << 3), None);
+        assert_eq!(Rules::default().with_flags(1 << 5), None);
+    }
+
+    #[test]
+    fn restart_ends_streak() {
+        let rules = Rules { scoring: Scoring::Streak, ..Rules::default() };
+        let mut engine = GameEngine::with_rules(vec![figure!(1, 3; (0, 0))], 42, rules);
+        for (index, row) in [9, 0].into_iter().enumerate() {
+            fill_row(engine.field_mut(), row, 5);
+            engine.place(index, coord!(5, row)).unwrap();
+        }
+        assert_eq!(engine.streak(), 2);
+
+        // first clear of the new game has no combo
+        engine.restart(7);
+        fill_row(engine.field_mut(), 9, 5);
+        engine.place(0, coord!(5, 9)).unwrap();
+        assert_eq!(engine.streak(), 1);
+        assert_eq!(engine.score(), BLOCK_COST_MULTIPLIER + LINE_MULTIPLIER);
+    }
+
+    #[test]
+    fn clear_cross() {
+        let mut field = Field::init_square(FIELD_LEN);
+        for i in 0..FIELD_LEN as i16 {
+            field.set(coord!(i, 4), 1);
+            field.set(coord!(7, i), 2);
+        }
+        field.set(coord!(0, 0), 3);
+
+        let (lines, blocks) = field.next_state().unwrap();
+        assert_eq!(lines, Lines { x: 1, y: 1 });
+        assert_eq!(blocks.len(), 2 * FIELD_LEN as usize - 1);
+        assert_eq!(field.blocks(), vec![Block { pos: coord!(0, 0), color: 3 }]);
+        assert_eq!(field.next_state(), None);
+    }
+
+    #[test]
+    fn clear_on_wide_board() {
+        let mut field = Field::new(coord!(14, 6));
+        for x in 0..14 {
+            field.set(coord!(x, 5), 1);
+        }
+        for y in 0..6 {
+            field.set(coord!(13, y), 2);
+        }
+        field.set(coord!(12, 4), 3);
+
+        let (lines, blocks) = field.next_state().unwrap();
+        assert_eq!(lines, Lines { x: 1, y: 1 });
+        assert_eq!(blocks.len(), 14 + 6 - 1);
+        assert_eq!(field.blocks(), vec![Block { pos: coord!(12, 4), color: 3 }]);
+        assert!(field.can_set_figure(coord!(9, 0), &figure!(1, 0; (0, 0), (4, 0))));
+        assert!(!field.can_set_figure(coord!(0, 2), &figure!(1, 0; (0, 0), (0, 4))));
+    }
+
+    #[test]
+    fn clear_with_blocked_cells() {
+        let mut field = Field::new(coord!(6, 5));
+        for x in 0..6 {
+            field.block(coord!(x, 0));
+        }
+        field.block(coord!(2, 3));
+        // row without playable cells is never full
+        assert_eq!(field.next_state(), None);
+
+        for x in [0, 1, 3, 4, 5] {
+            field.set(coord!(x, 3), 1);
+        }
+        field.set(coord!(2, 3), 2);
+        assert!(!field.can_set_figure(coord!(1, 0), &figure!(1, 0; (0, 0))));
+        assert_eq!(field.free_cells(), 30 - 7 - 5);
+
+        let (lines, blocks) = field.next_state().unwrap();
+        assert_eq!(lines, Lines { x: 0, y: 1 });
+        assert_eq!(blocks.len(), 5);
+        assert!(field.is_empty() && field.is_blocked(&coord!(2, 3)));
+    }
+
+    #[test]
+    fn board_size() {
+        assert_eq!(parse_board("12"), Ok(coord!(12, 12)));
+        assert_eq!(parse_board("10x14"), Ok(coord!(10, 14)));
+        for value in ["4", "17", "8x", "x", "16x17", "ten"] {
+            assert!(parse_board(value).is_err(), "{}", value);
+        }
+
+        let rules = Rules { board: coord!(16, 16), ..Rules::default() };
+        let engine = GameEngine::with_rules(default_figures(), 1, rules);
+        assert_eq!(engine.field().field_size, coord!(16, 16));
+        assert_eq!(engine.field().free_cells(), 256);
+    }
+
+    #[test]
+    fn figure_at_the_edge() {
+        let field = Field::init_square(FIELD_LEN);
+        let figure = figure!(1, 0; (0, 0), (1, 0), (2, 0), (2, 1));
+        assert!(field.can_set_figure(coord!(7, 8), &figure));
+        assert!(!field.can_set_figure(coord!(8, 8), &figure));
+        assert!(!field.can_set_figure(coord!(7, 9), &figure));
+        assert!(!field.can_set_figure(coord!(-1, 0), &figure));
+    }
+
+    #[test]
+    fn refill_after_last_figure() {
+        let mut engine = GameEngine::new(vec![figure!(1, 0; (0, 0))], 42);
+        for index in 0..BASKET_COUNT as usize {
+            let events = engine.place(index, coord!(index as i16, 0)).unwrap();
+            assert_eq!(events.contains(&Event::Refilled), index + 1 == BASKET_COUNT as usize);
+        }
+        assert_eq!(engine.basket().figures().len(), BASKET_COUNT as usize);
+    }
+
+    #[test]
+    fn game_over() {
+        let mut engine = GameEngine::new(vec![figure!(1, 0; (0, 0), (1, 0))], 42);
+        // chessboard pattern leaves no place for horizontal line
+        for y in 0..FIELD_LEN as i16 {
+            for x in 0..FIELD_LEN as i16 {
+                if (x + y) % 2 == 0 {
+                    engine.field_mut().set(coord!(x, y), 0);
+                }
+            }
+        }
+        assert!(engine.is_game_over());
+        assert_eq!(engine.place(0, coord!(1, 0)), Err(PlaceError::GameOver));
+    }
+
+    fn play_greedy(engine: &mut GameEngine) -> Vec<(usize, Coord)> {
+        let mut moves = Vec::new();
+        'game: while !engine.is_game_over() {
+            for index in 0..engine.basket().len() {
+                for y in 0..FIELD_LEN as i16 {
+                    for x in 0..FIELD_LEN as i16 {
+                        if engine.place(index, coord!(x, y)).is_ok() {
+                            moves.push((index, coord!(x, y)));
+                            continue 'game;
+                        }
+                    }
+                }
+            }
+            unreachable!();
+        }
+        moves
+    }
+
+    #[test]
+    fn same_seed_same_game() {
+        let mut first = GameEngine::new(default_figures(), 1010);
+        let moves = play_greedy(&mut first);
+
+        let mut second = GameEngine::new(default_figures(), 0);
+        second.restart(1010);
+        for (index, cell) in moves {
+            second.place(index, cell).unwrap();
+        }
+        assert!(second.is_game_over());
+        assert_eq!(first.score(), second.score());
+        assert_eq!(first.random_state(), second.random_state());
+        assert_eq!(first.field().blocks(), second.field().blocks());
+        assert_eq!(first.basket().figures(), second.basket().figures());
+    }
+
+    #[test]
+    fn restore_random_state() {
+        let mut engine = GameEngine::new(default_figures(), 42);
+        let mut restored = GameEngine::new(default_figures(), 7);
+        restored.restore_random(engine.seed(), engine.random_state());
+        restored.basket_mut().set_next(engine.basket().next().to_vec());
+
+        engine.basket_mut().clear();
+        engine.basket_mut().rnd_fill(&default_figures());
+        restored.basket_mut().clear();
+        restored.basket_mut().rnd_fill(&default_figures());
+        assert_eq!(restored.seed(), 42);
+        assert_eq!(engine.basket().figures(), restored.basket().figures());
+    }
+
+    #[test]
+    fn play_full_game() {
+        let mut engine = GameEngine::new(default_figures(), 1010);
+        let moves = play_greedy(&mut engine).len() as u32;
+        assert!(moves > 0);
+        assert!(engine.score() >= moves * BLOCK_COST_MULTIPLIER);
+    }
+
+    fn refill_ids(basket: &mut BasketSystem, figures: &[Figure]) -> Vec<u8> {
+        basket.clear();
+        basket.rnd_fill(figures);
+        basket.figures().iter().map(|figure| figure.index).collect()
+    }
+
+    #[test]
+    fn bag_policy() {
+        let figures = default_figures();
+        let mut basket = BasketSystem::new(BASKET_COUNT, 1010, Policy::Bag);
+        // 23 figures in 3 baskets
+        let mut ids: Vec<u8> = (0..23).flat_map(|_| refill_ids(&mut basket, &figures)).collect();
+        ids.truncate(figures.len() * 3);
+        for chunk in ids.chunks(figures.len()) {
+            let mut chunk = chunk.to_vec();
+            chunk.sort();
+            assert_eq!(chunk, (1..=figures.len() as u8).collect::<Vec<_>>());
+        }
+    }
+
+    #[test]
+    fn weighted_and_no_repeat_policy() {
+        let figures = vec![figure!(1, 0; (0, 0)).with_weight(0), figure!(2, 0; (0, 0)), figure!(3, 0; (0, 0))];
+        let mut weighted = BasketSystem::new(BASKET_COUNT, 42, Policy::Weighted);
+        let mut no_repeat = BasketSystem::new(BASKET_COUNT, 42, Policy::NoRepeat);
+        for _ in 0..50 {
+            assert!(!refill_ids(&mut weighted, &figures).contains(&1));
+            let mut ids = refill_ids(&mut no_repeat, &figures);
+            ids.sort();
+            assert_eq!(ids, vec![1, 2, 3]);
+        }
+    }
+
+    #[test]
+    fn restore_bag() {
+        let figures = default_figures();
+        let mut basket = BasketSystem::new(BASKET_COUNT, 7, Policy::Bag);
+        refill_ids(&mut basket, &figures);
+
+        let mut restored = BasketSystem::new(BASKET_COUNT, basket.random_state(), Policy::Uniform);
+        restored.restore_policy(basket.policy(), basket.bag().to_vec());
+        restored.set_next(basket.next().to_vec());
+        for _ in 0..10 {
+            assert_eq!(refill_ids(&mut basket, &figures), refill_ids(&mut restored, &figures));
+        }
+    }
+
+    #[test]
+    fn fair_deal() {
+        let figures = vec![figure!(1, 0; (0, 0)), figure!(2, 0; (0, 0), (1, 0), (0, 1), (1, 1))];
+        let deal = |fair| {
+            let mut engine = GameEngine::with_rules(figures.clone(), 1010, Rules { fair, ..Rules::default() });
+            checkerboard(engine.field_mut());
+            engine.basket_mut().clear();
+            engine.basket_mut().set(0, figures[0].clone());
+            // preview was rolled for the empty field, the dealt figures fit into the checkerboard
+            assert!(engine.place(0, coord!(1, 0)).unwrap().contains(&Event::Refilled));
+            let ids = |figures: &[Figure]| figures.iter().map(|figure| figure.index).collect::<Vec<_>>();
+            (ids(&engine.basket().figures()), ids(engine.basket().next()))
+        };
+        assert_eq!(deal(true), (vec![1, 1, 1], vec![1, 1, 1]));
+        let (dealt, next) = deal(false);
+        assert!(dealt.contains(&2) || next.contains(&2));
+    }
+
+    #[test]
+    fn fair_preview() {
+        let (single, square) = (figure!(1, 0; (0, 0)), figure!(2, 0; (0, 0), (1, 0), (0, 1), (1, 1)));
+        let engine = |fair| {
+            let rules = Rules { fair, ..Rules::default() };
+            let mut engine = GameEngine::with_rules(vec![single.clone(), square.clone()], 1010, rules);
+            engine.basket_mut().clear();
+            engine.basket_mut().set(0, single.clone());
+            engine
+        };
+        // the preview which fits is dealt as shown
+        for fair in [false, true] {
+            let mut engine = engine(fair);
+            let shown = engine.basket().next().to_vec();
+            engine.place(0, coord!(0, 0)).unwrap();
+            assert_eq!(engine.basket().figures(), shown);
+        }
+
+        // the preview which does not fit anymore is rolled again, the next one is shown instead
+        let mut engine = engine(true);
+        engine.basket_mut().set_next(vec![square.clone(); 3]);
+        checkerboard(engine.field_mut());
+        engine.place(0, coord!(1, 0)).unwrap();
+        assert_eq!(engine.basket().figures(), vec![single.clone(); 3]);
+        assert_eq!(engine.basket().next(), vec![single; 3]);
+    }
+
+    #[test]
+    fn fair_deal_with_hold() {
+        let (single, square) = (figure!(1, 0; (0, 0)), figure!(2, 0; (0, 0), (1, 0), (0, 1), (1, 1)));
+        let rules = Rules { fair: true, hold: true, ..Rules::default() };
+        let mut engine = GameEngine::with_rules(vec![single.clone(), square.clone()], 7, rules);
+        checkerboard(engine.field_mut());
+        let triple = [single.clone(), single.clone(), square];
+        engine.basket_mut().clear();
+        assert!(!engine.is_fair(&triple));
+        // the held block is placed instead of the square
+        engine.basket_mut().set(HOLD_INDEX, single);
+        assert!(engine.is_fair(&triple));
+    }
+
+    #[test]
+    fn rotate_and_mirror() {
+        // ##.
+        // .##
+        let figure = figure!(1, 0; (0, 0), (1, 0), (1, 1), (2, 1));
+        assert_eq!(figure.rotate(), figure!(1, 0; (1, 0), (0, 1), (1, 1), (0, 2)));
+        assert_eq!(figure.mirror(), figure!(1, 0; (1, 0), (2, 0), (0, 1), (1, 1)));
+        assert_eq!(figure.rotations().len(), 2);
+        assert_eq!(figure.rotate().rotate(), figure);
+        assert_eq!(figure.rotate().turns_from(&figure), Some(1));
+        assert_eq!(figure.mirror().turns_from(&figure), None);
+    }
+
+    #[test]
+    fn canonical_default_figures() {
+        let canonical = canonical_figures(&default_figures());
+        let ids: Vec<_> = canonical.iter().map(|figure| figure.index).collect();
+        assert_eq!(ids, vec![1, 2, 3, 4, 6, 8, 10, 12, 16, 20]);
+        let shapes = canonical.iter().map(|figure| figure.rotations().len()).sum::<usize>();
+        assert_eq!(shapes, default_figures().len());
+    }
+
+    #[test]
+    fn rotation_rule() {
+        let rules = Rules { rotation: true, ..Rules::default() };
+        let mut engine = GameEngine::with_rules(default_figures(), 1010, rules);
+        assert!(
+            engine.basket().figures().iter().all(|figure| [1, 2, 3, 4, 6, 8, 10, 12, 16, 20].contains(&figure.index))
+        );
+
+        // only a vertical line fits into the left column
+        for y in 0..FIELD_LEN as i16 {
+            for x in 1..FIELD_LEN as i16 {
+                engine.field_mut().set(coord!(x, y), 0);
+            }
+        }
+        engine.basket_mut().clear();
+        engine.basket_mut().set(0, default_figures()[4].clone());
+        assert!(!engine.is_game_over());
+        assert_eq!(engine.place(0, coord!(0, 0)), Err(PlaceError::CannotPlace));
+        assert!(engine.rotate(0));
+        assert!(engine.place(0, coord!(0, 0)).is_ok());
+
+        let mut classic = GameEngine::new(default_figures(), 1010);
+        assert!(!classic.rotate(0));
+    }
+
+    #[test]
+    fn hold_slot() {
+        let figures = vec![figure!(1, 0; (0, 0)), figure!(2, 1; (0, 0), (1, 0))];
+        let mut classic = GameEngine::new(figures.clone(), 42);
+        assert_eq!(classic.hold(0), Err(PlaceError::NoHold));
+
+        let rules = Rules { hold: true, ..Rules::default() };
+        let mut engine = GameEngine::with_rules(figures.clone(), 42, rules);
+        for index in 0..engine.basket().len() {
+            engine.basket_mut().set(index, figures[index % 2].clone());
+        }
+        assert_eq!(engine.hold(0), Ok(vec![Event::Held { basket: 0 }]));
+        assert_eq!(engine.basket().get(0), None);
+        assert_eq!(engine.basket().hold(), Some(&figures[0]));
+        assert_eq!(engine.hold(0), Err(PlaceError::EmptyBasket));
+
+        // swap and place from the hold slot
+        assert!(engine.hold(1).is_ok());
+        assert_eq!(engine.basket().get(1), Some(&figures[0]));
+        assert!(engine.place(HOLD_INDEX, coord!(0, 0)).is_ok());
+        assert_eq!(engine.basket().hold(), None);
+
+        // parking the last figure refills the basket
+        engine.place(1, coord!(5, 5)).unwrap();
+        assert!(engine.hold(2).unwrap().contains(&Event::Refilled));
+        assert_eq!(engine.basket().figures().len(), 3);
+
+        // held figure keeps the game going
+        let mut field = Field::init_square(FIELD_LEN);
+        for y in 0..FIELD_LEN as i16 {
+            for x in (y % 2..FIELD_LEN as i16).step_by(2) {
+                field.set(coord!(x, y), 0);
+            }
+        }
+        *engine.field_mut() = field;
+        engine.basket_mut().clear();
+        engine.basket_mut().set(0, figures[1].clone());
+        assert!(engine.is_game_over());
+        engine.basket_mut().set(HOLD_INDEX, figures[0].clone());
+        assert!(!engine.is_game_over());
+    }
+
+    #[test]
+    fn preview_is_dealt() {
+        let mut engine = GameEngine::new(default_figures(), 1010);
+        for _ in 0..5 {
+            let next = engine.basket().next().to_vec();
+            assert_eq!(next.len(), BASKET_COUNT as usize);
+            engine.basket_mut().clear();
+            engine.basket_mut().rnd_fill(&default_figures());
+            assert_eq!(engine.basket().figures(), next);
+        }
+
+        // preview doesn't change the figure sequence
+        let mut lazy = GameEngine::new(default_figures(), 1010);
+        lazy.basket_mut().set_next(Vec::new());
+        let mut rolled = lazy.clone();
+        rolled.preroll();
+        for engine in [&mut lazy, &mut rolled] {
+            engine.basket_mut().clear();
+            engine.basket_mut().rnd_fill(&default_figures());
+        }
+        assert_eq!(lazy.basket().figures(), rolled.basket().figures());
+    }
+
+    #[test]
+    fn fixed_sequence() {
+        let mut engine = GameEngine::new(default_figures(), 1010);
+        engine.set_sequence(vec![3, 5, 3, 9]);
+        let ids = |figures: &[Figure]| figures.iter().map(|figure| figure.index).collect::<Vec<_>>();
+        assert_eq!(ids(&engine.basket().figures()), vec![3, 5, 3]);
+        assert_eq!(ids(engine.basket().next()), vec![9]);
+
+        for (index, cell) in [(0, coord!(0, 0)), (1, coord!(0, 1)), (2, coord!(0, 2))] {
+            engine.place(index, cell).unwrap();
+        }
+        assert_eq!(ids(&engine.basket().figures()), vec![9]);
+        assert_eq!(engine.basket().sequence(), Some(&[][..]));
+
+        // the last figure is placed and nothing is dealt
+        let events = engine.place(0, coord!(0, 3)).unwrap();
+        assert!(events.contains(&Event::Refilled));
+        assert!(engine.is_out_of_figures());
+        assert_eq!(engine.place(0, coord!(0, 4)), Err(PlaceError::EmptyBasket));
+    }
+
+    #[test]
+    fn skip_triple() {
+        let mut engine = GameEngine::new(default_figures(), 1010);
+        engine.place(0, coord!(0, 0)).unwrap();
+        let (score, blocks) = (engine.score(), engine.field().blocks());
+        let next = engine.basket().next().to_vec();
+        assert_eq!(engine.skip(), vec![Event::Refilled]);
+        assert_eq!(engine.basket().figures(), next);
+        assert_eq!((engine.score(), engine.field().blocks()), (score, blocks));
+    }
+}
@@ -0,0 +1,220 @@
+use std::fs;
+use std::path::Path;
+
+use crate::engine::{self, FIGURE_COLORS, FIGURE_LEN, Figure};
+
+// figure id is stored in 5 bits, 0 is an empty basket
+pub const MAX_FIGURE_ID: u8 = 31;
+// keeps the bag small enough for saves
+pub const MAX_FIGURE_WEIGHT: u32 = 255;
+pub const DEFAULT_SET_NAME: &str = "classic";
+
+const COMMENT: char = ';';
+const BLOCK: char = '#';
+const EMPTY: char = '.';
+
+// set of the game figures
+//
+// file format:
+//   ; comment
+//   name <set name>
+//
+//   figure <id> [color <index>] [weight <value>]
+//   ##.
+//   .##
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FigureSet {
+    pub name: String,
+    pub figures: Vec<Figure>,
+}
+
+// figure header and shape lines
+struct Draft {
+    line: usize,
+    index: u8,
+    color: u8,
+    weight: u32,
+    shape: Vec<String>,
+}
+
+impl Default for FigureSet {
+    fn default() -> FigureSet {
+        FigureSet { name: DEFAULT_SET_NAME.to_string(), figures: engine::default_figures() }
+    }
+}
+
+impl Draft {
+    fn parse_header(line: usize, text: &str) -> Result<Draft, String> {
+        let mut words = text.split_whitespace().skip(1);
+        let index = number(line, "figure id", words.next())?;
+        let mut draft = Draft { line, index, color: 0, weight: 1, shape: Vec::new() };
+        while let Some(key) = words.next() {
+            match key {
+                "color" => draft.color = number(line, key, words.next())?,
+                "weight" => draft.weight = number(line, key, words.next())?,
+                _ => return Err(format!("line {}: unknown figure param `{}`", line, key)),
+            }
+        }
+        Ok(draft)
+    }
+
+    fn build(self) -> Result<Figure, String> {
+        let line = self.line;
+        if self.index == 0 || self.index > MAX_FIGURE_ID {
+            return Err(format!("line {}: figure id must be in 1..={}", line, MAX_FIGURE_ID));
+        }
+        if self.weight > MAX_FIGURE_WEIGHT {
+            return Err(format!("line {}: figure weight must be less than {}", line, MAX_FIGURE_WEIGHT + 1));
+        }
+        if self.color >= FIGURE_COLORS {
+            return Err(format!("line {}: figure color must be less than {}", line, FIGURE_COLORS));
+        }
+
+        let mut blocks = Vec::new();
+        for (y, row) in self.shape.iter().enumerate() {
+            for (x, c) in row.chars().enumerate() {
+                match c {
+                    BLOCK => blocks.push(coord!(x as i16, y as i16)),
+                    EMPTY => (),
+                    _ => return Err(format!("line {}: unknown shape symbol `{}`", line + y + 1, c)),
+                }
+            }
+        }
+        if blocks.is_empty() {
+            return Err(format!("line {}: figure {} has no blocks", line, self.index));
+        }
+
+        // move shape to the top left corner
+        let min = blocks.iter().fold(blocks[0], |m, p| coord!(m.x.min(p.x), m.y.min(p.y)));
+        let blocks: Vec<_> = blocks.into_iter().map(|p| p - min).collect();
+        if blocks.iter().any(|p| p.x >= FIGURE_LEN as i16 || p.y >= FIGURE_LEN as i16) {
+            return Err(format!("line {}: figure {} is bigger than {}x{}", line, self.index, FIGURE_LEN, FIGURE_LEN));
+        }
+        Ok(Figure::from_slice(self.index, &blocks, self.color).with_weight(self.weight))
+    }
+}
+
+impl FigureSet {
+    pub fn parse(data: &str) -> Result<FigureSet, String> {
+        let mut name = None;
+        let mut drafts: Vec<Draft> = Vec::new();
+
+        for (number, text) in data.lines().enumerate() {
+            let line = number + 1;
+            let text = text.split(COMMENT).next().unwrap_or_default().trim();
+            let keyword = text.split_whitespace().next().unwrap_or_default();
+            match keyword {
+                "" => (),
+                "name" => name = Some(text[keyword.len()..].trim().to_string()),
+                "figure" => drafts.push(Draft::parse_header(line, text)?),
+                _ if text.starts_with([BLOCK, EMPTY]) => match drafts.last_mut() {
+                    Some(draft) => draft.shape.push(text.to_string()),
+                    None => return Err(format!("line {}: shape without figure header", line)),
+                },
+                _ => return Err(format!("line {}: unknown keyword `{}`", line, keyword)),
+            }
+        }
+
+        let mut figures: Vec<Figure> = Vec::new();
+        for draft in drafts {
+            let figure = draft.build()?;
+            if figures.iter().any(|f| f.index == figure.index) {
+                return Err(format!("figure id {} is used twice", figure.index));
+            }
+            figures.push(figure);
+        }
+        if figures.iter().all(|figure| figure.weight() == 0) {
+            return Err("figure set has no figures to spawn".to_string());
+        }
+
+        let name = name.filter(|name| !name.is_empty()).unwrap_or_else(|| "custom".to_string());
+        Ok(FigureSet { name, figures })
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<FigureSet, String> {
+        let data = fs::read_to_string(path).map_err(|e| e.to_string())?;
+        FigureSet::parse(&data)
+    }
+
+    pub fn id(&self) -> u32 {
+        set_id(&self.figures)
+    }
+}
+
+pub(crate) fn number<T: std::str::FromStr>(line: usize, name: &str, value: Option<&str>) -> Result<T, String> {
+    value.and_then(|v| v.parse().ok()).ok_or_else(|| format!("line {}: bad or missing value for `{}`", line, name))
+}
+
+// FNV-1a hash of the figures, name is ignored
+pub fn set_id(figures: &[Figure]) -> u32 {
+    let mut sorted: Vec<_> = figures.iter().collect();
+    sorted.sort_by_key(|figure| figure.index);
+
+    let mut hash: u32 = 0x811c9dc5;
+    let mut feed = |bytes: &[u8]| {
+        for byte in bytes {
+            hash ^= *byte as u32;
+            hash = hash.wrapping_mul(0x01000193);
+        }
+    };
+    for figure in sorted {
+        feed(&[figure.index, figure.color()]);
+        feed(&figure.weight().to_le_bytes());
+        for pos in figure.iter() {
+            feed(&[pos.x as u8, pos.y as u8]);
+        }
+    }
+    hash
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::figure;
+
+    #[test]
+    fn resource_file_is_default_set() {
+        let set = FigureSet::parse(include_str!("../resources/figures.txt")).unwrap();
+        assert_eq!(set.name, DEFAULT_SET_NAME);
+        assert_eq!(set, FigureSet::default());
+        assert_eq!(set.id(), FigureSet::default().id());
+    }
+
+    #[test]
+    fn parse_figure() {
+        let data = "name test\n\nfigure 7 color 2 weight 3 ; corner\n..#\n.##\n";
+        let set = FigureSet::parse(data).unwrap();
+        let figure = &set.figures[0];
+        assert_eq!(figure, &figure!(7, 2; (1, 0), (0, 1), (1, 1)).with_weight(3));
+        assert_eq!(set.name, "test");
+    }
+
+    #[test]
+    fn id_depends_on_figures() {
+        let one = FigureSet::parse("figure 1\n#").unwrap();
+        let other = FigureSet::parse("figure 1 weight 2\n#").unwrap();
+        assert_ne!(one.id(), other.id());
+        assert_eq!(one.id(), FigureSet::parse("name x\nfigure 1\n.#").unwrap().id());
+    }
+
+    #[test]
+    fn validation() {
+        let errors = [
+            "figure 0\n#",
+            "figure 32\n#",
+            "figure 1 color 8\n#",
+            "figure 1\n..",
+            "figure 1\n######",
+            "figure 1\n#x",
+            "figure 1\n#\nfigure 1\n#",
+            "figure 1 weight 0\n#",
+            "figure 1 weight 256\n#",
+            "figure 1 size 2\n#",
+            "#\nfigure 1",
+            "",
+        ];
+        for data in errors {
+            assert!(FigureSet::parse(data).is_err(), "{:?}", data);
+        }
+    }
+}
@@ -1,18 +1,27 @@
-use std::collections::{HashMap, HashSet};
-use std::mem;
+use std::collections::HashMap;
 use std::time::{Duration, SystemTime};
 
 use sdl2::pixels::Color;
 use sdl2::render::Canvas;
+use sdl2::surface::Surface;
+use sdl2::ttf::Font;
 use sdl2::video::Window;
 
-use crate::consts::{FAKE_K, GET_COLOR_ERROR, MILLISECOND, TILE_CLEAN_ANIMATION_SIZE};
-use crate::extra::{fake_contrast, BlendColor, Coord, RectData};
-use crate::random::Random;
+use rs_1010::board::{BoardMask, Cell};
+use rs_1010::coord::Coord;
+use rs_1010::engine::{BASKET_COUNT, BasketSystem, Block, Field, Figure, GameEngine};
+use rs_1010::history::History;
+use rs_1010::replay::Replay;
+use rs_1010::scoring::Points;
+use rs_1010::slots::DAILY_MODE;
+
+use crate::consts::{
+    BASKET_HEIGHT, BASKET_WIDTH, FAKE_K, FIELD_AREA, FIELD_BASKET_SEP, FIELD_SHIFT_HEIGHT, FIELD_SHIFT_WIDTH,
+    FONT_HEIGHT, GET_COLOR_ERROR, LABEL_FRAMES, MILLISECOND, MINIMAL_TILE_SIZE, TILE_SEP_1, TILE_SIZE_1,
+};
+use crate::extra::{BlendColor, RectData, fake_contrast};
 use crate::render::*;
 
-type Blocks = HashSet<Coord>;
-
 #[derive(Copy, Clone, PartialEq, Eq)]
 pub enum GameState {
     Idle,
@@ -20,8 +29,27 @@ pub enum GameState {
     UsernameInput,
     HighscoreTable,
     GameOver,
+    LevelSelect,
+    ModeSelect,
+    SlotSelect,
+    SlotName,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Mode {
+    // game without time limits
+    Endless,
+    // score as much as possible in this number of minutes
+    Timed(u32),
+    // basket figures are lost when nothing is placed in time
+    Blitz,
+    // the same figures for everyone on this day
+    Daily,
 }
 
+// modes in the order of the mode select
+pub const MODES: [Mode; 5] = [Mode::Endless, Mode::Timed(3), Mode::Timed(5), Mode::Blitz, Mode::Daily];
+
 pub struct GameTime {
     elapsed: Duration,
     time: Option<SystemTime>,
@@ -33,52 +61,68 @@ pub struct FPSLimiter {
     last_ticks: u32,
 }
 
-#[derive(Clone, Copy)]
-pub struct Lines {
-    pub x: u32,
-    pub y: u32,
-}
-
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 enum State {
     Wait,
     Clear(u8),
 }
 
-pub struct Field {
+pub struct FieldView {
     pub textures: HashMap<i16, RectData>,
-    pub field_size: Coord,
+    board: BoardMask,
+    field_size: Coord,
     tile_size: Coord,
     tile_sep: Coord,
     pos: Coord,
-    field: HashSet<Coord>,
-    colors: HashMap<Coord, Color>,
     state: State,
-    clear: Blocks,
-    lines: Lines,
+    clear: HashMap<Coord, u8>,
+    // steps of the clear animation
+    clean_size: u8,
+    labels: Vec<Label>,
 }
 
-#[derive(Clone, Eq, PartialEq)]
-pub struct Figure {
-    blocks: HashSet<Coord>,
-    color: Color,
-    // not the best way to identify figure
-    pub index: u8,
+// score text which rises over the cleared lines
+struct Label {
+    rows: Vec<String>,
+    pos: Coord,
+    age: u8,
 }
 
-pub struct Basket {
-    field_size: Coord,
-    tile_size: Coord,
-    tile_sep: Coord,
-    figure: Option<Figure>,
-    pos: Coord,
+// positions of the game objects for the board size
+pub struct Layout {
+    pub tile_size: u8,
+    pub field: Coord,
+    pub basket: Coord,
+    pub basket_shift: Coord,
+    pub hold: Coord,
+    pub preview: Coord,
+    pub score: Coord,
+    pub highscore: Coord,
+    pub timer: Coord,
+    pub separator: Coord,
+}
+
+// game which waits while other one is played
+pub struct SavedGame {
+    pub engine: GameEngine,
+    pub history: History,
+    pub game_time: GameTime,
+    pub hint_count: u32,
+    pub recorder: Option<Replay>,
 }
 
-pub struct BasketSystem {
+pub struct BasketView {
+    // hold slot is the last one
     basket: Vec<Basket>,
-    current: Option<usize>,
-    rnd: Random,
     texture: RectData,
+    hold: bool,
+}
+
+struct Basket {
+    field_size: Coord,
+    tile_size: Coord,
+    tile_sep: Coord,
+    pos: Coord,
 }
 
 impl GameTime {
@@ -113,10 +157,58 @@ impl GameTime {
         self.elapsed.as_secs()
     }
 
+    pub fn elapsed_millis(&self) -> u32 {
+        self.elapsed.as_millis() as u32
+    }
+
     pub fn format(&self) -> String {
-        let seconds = self.elapsed_seconds();
-        let (hours, minutes, seconds) = (seconds / (60 * 60), (seconds / 60) % 60, seconds % 60);
-        format!("{:02}:{:02}:{:02}", hours, minutes, seconds)
+        format_seconds(self.elapsed_seconds())
+    }
+}
+
+pub fn format_seconds(seconds: u64) -> String {
+    let (hours, minutes, seconds) = (seconds / (60 * 60), (seconds / 60) % 60, seconds % 60);
+    format!("{:02}:{:02}:{:02}", hours, minutes, seconds)
+}
+
+impl Mode {
+    // id of the mode in the config
+    pub fn name(&self) -> String {
+        match self {
+            Mode::Endless => "endless".to_string(),
+            Mode::Timed(minutes) => format!("timed{}", minutes),
+            Mode::Blitz => "blitz".to_string(),
+            Mode::Daily => DAILY_MODE.to_string(),
+        }
+    }
+
+    pub fn title(&self) -> String {
+        match self {
+            Mode::Endless => "endless".to_string(),
+            Mode::Timed(minutes) => format!("timed, {} minutes", minutes),
+            Mode::Blitz => "blitz, move timer".to_string(),
+            Mode::Daily => "daily challenge".to_string(),
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Mode> {
+        MODES.into_iter().find(|mode| mode.name() == name)
+    }
+
+    // config item of the saved game in the old versions, endless game uses the first one
+    pub fn state_key(&self) -> String {
+        match self {
+            Mode::Endless => "state".to_string(),
+            _ => format!("state_{}", self.name()),
+        }
+    }
+
+    // seconds left in the timed game
+    pub fn time_left(&self, game_time: &GameTime) -> Option<u64> {
+        match self {
+            Mode::Timed(minutes) => Some((*minutes as u64 * 60).saturating_sub(game_time.elapsed_seconds())),
+            _ => None,
+        }
     }
 }
 
@@ -144,18 +236,50 @@ impl FPSLimiter {
     }
 }
 
-impl Lines {
-    pub fn empty() -> Lines {
-        Lines { x: 0, y: 0 }
+impl Layout {
+    pub fn new(board: Coord) -> Layout {
+        let step = (FIELD_AREA as i16 / board.x.max(board.y)).min((TILE_SIZE_1 + TILE_SEP_1) as i16);
+        // clear animation changes tile size by 2
+        let tile_size = (step - TILE_SEP_1 as i16) as u8 & !1;
+        let size = board * (tile_size + TILE_SEP_1) as i16;
+        // field is centered in its area and baskets are next to it
+        let field = coord!(FIELD_SHIFT_WIDTH, FIELD_SHIFT_HEIGHT) + ((coord!(FIELD_AREA as i16) - size) >> 1_i16);
+        let right = field.x + size.x + FIELD_BASKET_SEP as i16;
+        let basket = coord!(right, FIELD_SHIFT_HEIGHT + 4 * FONT_HEIGHT);
+        let basket_shift = coord!(0, BASKET_HEIGHT as i16);
+        let score = coord!(right + 3, FIELD_SHIFT_HEIGHT - 3);
+        let line = coord!(0, FONT_HEIGHT - 1);
+        Layout {
+            tile_size,
+            field,
+            basket,
+            basket_shift,
+            hold: coord!((field.x - BASKET_WIDTH as i16) / 2, basket.y),
+            preview: basket + basket_shift * BASKET_COUNT as i16,
+            score,
+            highscore: score + line,
+            timer: score + line * 2_i16,
+            separator: score + line * 3_i16,
+        }
     }
+}
 
-    pub fn not_empty(self) -> bool {
-        self.x != 0 || self.y != 0
+impl SavedGame {
+    // exchange the played game with the saved one
+    pub fn swap(
+        &mut self, engine: &mut GameEngine, history: &mut History, game_time: &mut GameTime, hint_count: &mut u32,
+        recorder: &mut Option<Replay>,
+    ) {
+        std::mem::swap(&mut self.engine, engine);
+        std::mem::swap(&mut self.history, history);
+        std::mem::swap(&mut self.game_time, game_time);
+        std::mem::swap(&mut self.hint_count, hint_count);
+        std::mem::swap(&mut self.recorder, recorder);
     }
 }
 
-impl Field {
-    pub fn init_square(pole_size: u8, tile_size: u8, tile_sep: u8, steps: i16, radius: i16, pos: Coord) -> Field {
+impl FieldView {
+    pub fn new(board: &BoardMask, tile_size: u8, tile_sep: u8, steps: i16, radius: i16, pos: Coord) -> FieldView {
         // alloc all size tiles
         let mut textures = HashMap::new();
         for i in (8..=tile_size + 2).step_by(2) {
@@ -163,32 +287,26 @@ impl Field {
             textures.insert(i as i16, block);
         }
 
-        Field {
-            field_size: coord!(pole_size as i16),
+        FieldView {
+            board: board.clone(),
+            field_size: board.size,
             tile_size: coord!(tile_size as i16),
             tile_sep: coord!(tile_sep as i16),
-            field: HashSet::new(),
-            colors: HashMap::new(),
             state: State::Wait,
-            clear: Blocks::new(),
-            lines: Lines::empty(),
+            clear: HashMap::new(),
             pos,
             textures,
+            clean_size: tile_size / 2 - MINIMAL_TILE_SIZE,
+            labels: Vec::new(),
         }
     }
 
-    pub fn set(&mut self, pos: Coord, color: Color) {
-        self.field.insert(pos);
-        self.colors.insert(pos, color);
+    pub fn tile_size(&self) -> Coord {
+        self.tile_size
     }
 
-    pub fn unset(&mut self, pos: &Coord) {
-        self.field.remove(pos);
-        self.colors.remove(pos);
-    }
-
-    pub fn is_set(&self, pos: &Coord) -> bool {
-        self.field.contains(pos)
+    pub fn tile_sep(&self) -> Coord {
+        self.tile_sep
     }
 
     pub fn get_cell_index(&self, pos: &Coord) -> Coord {
@@ -200,8 +318,8 @@ impl Field {
         norm * (self.tile_size + self.tile_sep) + self.pos
     }
 
-    pub fn get_color(&self, pos: &Coord) -> Option<&Color> {
-        self.colors.get(pos)
+    pub fn get_cell_point(&self, cell: Coord) -> Coord {
+        cell * (self.tile_size + self.tile_sep) + self.pos
     }
 
     pub fn is_point_in(&self, pos: &Coord) -> bool {
@@ -209,154 +327,96 @@ impl Field {
         v.x >= 0 && v.x < self.field_size.x && v.y >= 0 && v.y < self.field_size.y
     }
 
-    pub fn set_figure(&mut self, pos: &Coord, figure: &Figure) -> bool {
-        if self.state != State::Wait {
-            return false;
-        }
-        let new_figure = figure.shift(self.get_cell_index(pos));
-        for Coord { x, y } in &new_figure.blocks {
-            if *x >= self.field_size.x || *x < 0 || *y >= self.field_size.y || *y < 0 {
-                return false;
-            }
-        }
-        let not_intersect = self.field.intersection(&new_figure.blocks).count() == 0;
-        if not_intersect {
-            for p in new_figure.blocks {
-                self.set(p, figure.color);
-            }
-        }
-        not_intersect
+    pub fn is_animated(&self) -> bool {
+        self.state != State::Wait
     }
 
-    fn check_line_h(&self, index: u8) -> Option<bool> {
-        if index > self.field_size.x as u8 {
-            return None;
+    // start clear animation for removed blocks
+    pub fn clear(&mut self, blocks: Vec<Block>) {
+        if blocks.is_empty() {
+            return;
         }
-        let mut counter = 0;
-        for i in 0..self.field_size.x {
-            if self.field.contains(&coord!(i, index as i16)) {
-                counter += 1;
-            }
+        self.state = State::Clear(self.clean_size);
+        for Block { pos, color } in blocks {
+            self.clear.insert(pos, color);
         }
-        Some(counter == self.field_size.x)
     }
 
-    fn check_line_v(&self, index: u8) -> Option<bool> {
-        if index > self.field_size.y as u8 {
-            return None;
+    // score breakdown of the move is shown at the center of the cleared blocks
+    pub fn show_points(&mut self, points: &Points) {
+        let (Some(lo), Some(hi)) = (
+            self.clear.keys().copied().reduce(|a, b| coord!(a.x.min(b.x), a.y.min(b.y))),
+            self.clear.keys().copied().reduce(|a, b| coord!(a.x.max(b.x), a.y.max(b.y))),
+        ) else {
+            return;
+        };
+        let step = self.tile_size + self.tile_sep;
+        let mut rows = vec![format!("+{}", points.lines)];
+        if points.combo > 0 {
+            rows.push(format!("streak {} +{}", points.streak, points.combo));
         }
-        let mut counter = 0;
-        for i in 0..self.field_size.y {
-            if self.field.contains(&coord!(index as i16, i)) {
-                counter += 1;
-            }
+        if points.perfect > 0 {
+            rows.push(format!("perfect +{}", points.perfect));
         }
-        Some(counter == self.field_size.y)
+        let pos = self.pos + (((lo + hi) * step + step) >> 1_i16) - coord!(0, FONT_HEIGHT * rows.len() as i16 / 2);
+        self.labels.push(Label { rows, pos, age: 0 });
     }
 
-    fn remove_blocks(&mut self) {
-        for p in mem::take(&mut self.clear) {
-            self.unset(&p);
-        }
+    // stop clear animation
+    pub fn reset(&mut self) {
+        self.state = State::Wait;
+        self.clear.clear();
+        self.labels.clear();
     }
 
-    pub fn can_set(&self, figures: Vec<Figure>) -> bool {
-        if figures.is_empty() || self.state != State::Wait {
-            return true;
+    pub fn next_state(&mut self) {
+        for label in &mut self.labels {
+            label.age += 1;
         }
-        for figure in figures {
-            let x_max = figure.blocks.iter().fold(0, |m, p| m.max(p.x));
-            let y_max = figure.blocks.iter().fold(0, |m, p| m.max(p.y));
-            for y in 0..self.field_size.y - y_max {
-                for x in 0..self.field_size.x - x_max {
-                    let new_figure = figure.shift(coord!(x, y));
-                    // can set a figure?
-                    if self.field.intersection(&new_figure.blocks).count() == 0 {
-                        return true;
-                    }
-                }
+        self.labels.retain(|label| label.age < LABEL_FRAMES);
+        self.state = match self.state {
+            State::Wait => State::Wait,
+            // animation step
+            State::Clear(p) if p > 0 => State::Clear(p - 1),
+            // animation is finished
+            State::Clear(_) => {
+                self.clear.clear();
+                State::Wait
             }
-        }
-        false
-    }
-
-    pub fn is_empty(&self) -> bool {
-        self.field.is_empty()
-    }
-
-    pub fn clear(&mut self) {
-        self.state = State::Clear(TILE_CLEAN_ANIMATION_SIZE);
-        self.lines = Lines::empty();
-        self.clear = Blocks::new();
-
-        for pos in &self.field {
-            self.clear.insert(*pos);
-        }
-    }
-
-    pub fn update_state(&mut self, x: i16, y: i16, p: u8) {
-        match &self.state {
-            State::Wait => self.state = State::Clear(p),
-            State::Clear(_) => {}
         };
-        self.clear.insert(coord!(x, y));
     }
 
-    pub fn next_state(&mut self) -> Option<Lines> {
-        let new_state = match self.state {
-            State::Wait => {
-                // calc x lines
-                for x in 0..self.field_size.x {
-                    if let Some(true) = self.check_line_v(x as u8) {
-                        for y in 0..self.field_size.y {
-                            self.update_state(x, y, TILE_CLEAN_ANIMATION_SIZE);
-                        }
-                        self.lines.x += 1;
-                    }
-                }
-                // and y
-                for y in 0..self.field_size.y {
-                    if let Some(true) = self.check_line_h(y as u8) {
-                        for x in 0..self.field_size.x {
-                            self.update_state(x, y, TILE_CLEAN_ANIMATION_SIZE);
-                        }
-                        self.lines.y += 1;
-                    }
-                }
-                self.state
-            }
-            State::Clear(p) => {
-                if p > 0 {
-                    // animation step
-                    State::Clear(p - 1)
-                } else {
-                    // animation is finished
-                    self.remove_blocks();
-                    State::Wait
-                }
-            }
-        };
-
-        self.state = new_state;
-        if self.state == State::Wait {
-            if self.lines.not_empty() {
-                let lines = self.lines;
-                self.lines = Lines::empty();
-                Some(lines)
-            } else {
-                None
+    // labels fade out while they rise
+    pub fn render_labels(&self, surface: &mut Surface, text: &Font, fg: Color, bg: Color) -> Result<(), String> {
+        for Label { rows, pos, age } in &self.labels {
+            let alpha = 255 - (*age as u32 * 255 / LABEL_FRAMES as u32) as u8;
+            let color = Color::RGBA(fg.r, fg.g, fg.b, alpha);
+            for (index, row) in rows.iter().enumerate() {
+                let (width, _) = text.size_of(row).map_err(|e| e.to_string())?;
+                let shift = coord!(width as i16 / 2, *age as i16 - FONT_HEIGHT * index as i16);
+                font(surface, text, *pos - shift, color, bg, row)?;
             }
-        } else {
-            None
         }
+        Ok(())
     }
 
-    pub fn render(&self, surface: &mut Canvas<Window>, empty: Color, bg: Color) -> Result<(), String> {
+    pub fn render(
+        &self, surface: &mut Canvas<Window>, field: &Field, palette: &[Color], free: Color, blocked: Color, bg: Color,
+    ) -> Result<(), String> {
         for y in 0..self.field_size.y {
             for x in 0..self.field_size.x {
                 let pos = coord!(x, y);
-                let color =
-                    if self.field.contains(&pos) { *self.colors.get(&pos).ok_or(GET_COLOR_ERROR)? } else { empty };
+                // cells out of the board are not drawn and blocked ones have own color
+                let empty = match self.board.get(&pos) {
+                    Some(Cell::Absent) => continue,
+                    Some(Cell::Blocked) => blocked,
+                    _ => free,
+                };
+                let color_index = field.get_color(&pos).or_else(|| self.clear.get(&pos).copied());
+                let color = match color_index {
+                    Some(index) => *palette.get(index as usize).ok_or(GET_COLOR_ERROR)?,
+                    None => empty,
+                };
                 let fg = fake_contrast(color, FAKE_K);
                 let fbe = fake_contrast(empty, FAKE_K);
 
@@ -364,8 +424,8 @@ impl Field {
                 // block shift size
                 let shift_pos = match &self.state {
                     State::Clear(p) => {
-                        if self.clear.contains(&coord!(x, y)) {
-                            coord!(TILE_CLEAN_ANIMATION_SIZE as i16 - *p as i16)
+                        if self.clear.contains_key(&pos) {
+                            coord!(self.clean_size as i16 - *p as i16)
                         } else {
                             coord!()
                         }
@@ -386,7 +446,7 @@ impl Field {
                 fill_rounded_rect_from(surface, &data, shadow_color)?;
 
                 // draw only set figures
-                if self.field.contains(&pos) {
+                if color_index.is_some() {
                     let tile = self.tile_size.x - 2 * shift_pos.x;
                     let data = self.textures[&tile].shift(position + shift_pos - 2_i16);
                     fill_rounded_rect_from(surface, &data, blend_color)?;
@@ -397,52 +457,24 @@ impl Field {
     }
 }
 
-impl Figure {
-    pub fn from_slice(index: u8, coords: &[Coord], color: Color) -> Figure {
-        let mut blocks = HashSet::new();
-        for p in coords {
-            blocks.insert(*p);
-        }
-        Figure { blocks, color, index }
-    }
-
-    pub fn shift(&self, pos: Coord) -> Figure {
-        let mut blocks = HashSet::new();
-        for block in &self.blocks {
-            blocks.insert(pos + *block);
-        }
-        Figure { blocks, color: self.color, index: self.index }
-    }
-
-    pub fn blocks(&self) -> u32 {
-        self.blocks.len() as u32
-    }
-
-    pub fn max(&self) -> Coord {
-        let (mut max_x, mut max_y) = (0, 0);
-        for Coord { x, y } in &self.blocks {
-            max_x = max_x.max(*x);
-            max_y = max_y.max(*y);
-        }
-        coord!(max_x, max_y)
-    }
-
-    pub fn render(
-        &self, surface: &mut Canvas<Window>, texture: &RectData, pos: Coord, size: Coord, sep: Coord, alpha: u8,
-    ) -> Result<(), String> {
-        let color = Color::RGBA(self.color.r, self.color.g, self.color.b, alpha);
-        let fake = fake_contrast(color, FAKE_K);
-        for c in &self.blocks {
-            let position = *c * (size + sep) + pos;
-            let tex = texture.shift(position);
-            // draw shadow
-            fill_rounded_rect_from(surface, &tex, fake.into())?;
-            let tex = texture.shift(position - 2_i16);
-            // draw figure
-            fill_rounded_rect_from(surface, &tex, color.into())?;
-        }
-        Ok(())
-    }
+#[allow(clippy::too_many_arguments)]
+pub fn render_figure(
+    surface: &mut Canvas<Window>, figure: &Figure, palette: &[Color], texture: &RectData, pos: Coord, size: Coord,
+    sep: Coord, alpha: u8,
+) -> Result<(), String> {
+    let color = *palette.get(figure.color() as usize).ok_or(GET_COLOR_ERROR)?;
+    let color = Color::RGBA(color.r, color.g, color.b, alpha);
+    let fake = fake_contrast(color, FAKE_K);
+    for c in figure.iter() {
+        let position = c * (size + sep) + pos;
+        let tex = texture.shift(position);
+        // draw shadow
+        fill_rounded_rect_from(surface, &tex, fake.into())?;
+        let tex = texture.shift(position - 2_i16);
+        // draw figure
+        fill_rounded_rect_from(surface, &tex, color.into())?;
+    }
+    Ok(())
 }
 
 impl Basket {
@@ -451,7 +483,6 @@ impl Basket {
             field_size: coord!(field_size as i16),
             tile_size: coord!(tile_size as i16),
             tile_sep: coord!(tile_sep as i16),
-            figure: None,
             pos,
         }
     }
@@ -462,28 +493,13 @@ impl Basket {
         pos.x >= p1.x && pos.x <= p2.x && pos.y >= p1.y && pos.y <= p2.y
     }
 
-    pub fn push(&mut self, figure: Figure) {
-        self.figure = Some(figure);
-    }
-
-    pub fn pop(&mut self) -> Option<Figure> {
-        self.figure.take()
-    }
-
-    pub fn remove(&mut self) {
-        self.figure = None;
-    }
-
-    pub fn figure(&self) -> Option<Figure> {
-        self.figure.clone()
-    }
-
     pub fn centering(&self, figure: &Figure) -> Coord {
         (self.field_size - figure.max()) >> 1_i16
     }
 
     pub fn render(
-        &self, surface: &mut Canvas<Window>, texture: &RectData, empty: Color, bg: Color,
+        &self, surface: &mut Canvas<Window>, texture: &RectData, figure: Option<&Figure>, palette: &[Color],
+        empty: Color, bg: Color,
     ) -> Result<(), String> {
         let wsize = self.tile_size + self.tile_sep;
         let fake = fake_contrast(empty, FAKE_K);
@@ -495,12 +511,12 @@ impl Basket {
                 fill_rounded_rect_from(surface, &tex, BlendColor::blend(fake, bg))?;
             }
         }
-        if let Some(figure) = &self.figure {
-            let color = figure.color;
+        if let Some(figure) = figure {
+            let color = *palette.get(figure.color() as usize).ok_or(GET_COLOR_ERROR)?;
             let fake = fake_contrast(color, FAKE_K);
             let cen = self.centering(figure);
-            for pos in &figure.blocks {
-                let position = (*pos + cen) * wsize + self.pos;
+            for pos in figure.iter() {
+                let position = (pos + cen) * wsize + self.pos;
                 // draw shadow
                 let tex = texture.shift(position);
                 fill_rounded_rect_from(surface, &tex, BlendColor::blend(fake, bg))?;
@@ -513,93 +529,58 @@ impl Basket {
     }
 }
 
-impl BasketSystem {
+impl BasketView {
     #[allow(clippy::too_many_arguments)]
     pub fn new(
         count: u8, field_size: u8, tile_size: u8, tile_sep: u8, steps: i16, radius: i16, pos: Coord, shift: Coord,
-    ) -> BasketSystem {
+    ) -> BasketView {
         let mut basket = Vec::new();
         let texture = build_rounded_rect(coord!(), coord!(tile_size as i16), steps, radius);
-        let seed = match SystemTime::now().duration_since(SystemTime::UNIX_EPOCH) {
-            Ok(n) => n.as_secs(),
-            // https://xkcd.com/221/
-            Err(_) => 4,
-        };
-        let rnd = Random::new(seed as u32);
         for i in 0..count {
             let bpos = pos + shift * (i as i16);
             basket.push(Basket::init_square(field_size, tile_size, tile_sep, bpos));
         }
-        BasketSystem { basket, current: None, rnd, texture }
-    }
-
-    pub fn get(&mut self, pos: Coord) -> Option<Figure> {
-        for (index, item) in self.basket.iter_mut().enumerate() {
-            if item.point_in(pos) {
-                self.current = Some(index);
-                return item.pop();
-            }
-        }
-        None
-    }
-
-    pub fn set(&mut self, index: usize, figure: Figure) {
-        self.basket[index].push(figure);
-    }
-
-    pub fn pop(&mut self, index: usize) {
-        self.basket[index].pop();
-    }
-
-    pub fn clear(&mut self) {
-        for fig in self.basket.iter_mut() {
-            fig.remove();
-        }
+        BasketView { basket, texture, hold: false }
     }
 
-    pub fn ret(&mut self, figure: Figure) {
-        if let Some(index) = self.current {
-            self.set(index, figure);
-            self.current = None;
-        }
+    // add hold slot after the baskets
+    pub fn with_hold(mut self, pos: Coord) -> BasketView {
+        let Basket { field_size, tile_size, tile_sep, .. } = self.basket[0];
+        self.basket.push(Basket { field_size, tile_size, tile_sep, pos });
+        self.hold = true;
+        self
     }
 
-    pub fn rnd_fill(&mut self, figures: &[Figure]) {
-        let size = figures.len();
-        for index in 0..self.basket.len() {
-            let item = self.rnd.rand() as usize % size;
-            self.set(index, figures[item].clone());
-        }
+    // cursor is over the hold slot
+    pub fn is_hold(&self, pos: Coord) -> bool {
+        self.hold && self.basket.last().is_some_and(|item| item.point_in(pos))
     }
 
-    pub fn check_and_refill(&mut self, figures: &[Figure]) {
-        for item in &self.basket {
-            if item.figure.is_some() {
-                return;
-            }
-        }
-        self.rnd_fill(figures);
+    // index of non empty basket under the cursor
+    pub fn get(&self, pos: Coord, system: &BasketSystem) -> Option<usize> {
+        let index = self.basket.iter().position(|item| item.point_in(pos))?;
+        system.get(index).map(|_| index)
     }
 
-    pub fn figures(&self) -> Vec<Figure> {
-        let mut figures = Vec::new();
-        for basket in &self.basket {
-            if let Some(figure) = basket.figure() {
-                figures.push(figure);
-            }
+    pub fn render(
+        &self, surface: &mut Canvas<Window>, system: &BasketSystem, current: Option<usize>, palette: &[Color],
+        empty_field_color: Color, bg_color: Color,
+    ) -> Result<(), String> {
+        for (index, item) in self.basket.iter().enumerate() {
+            // selected figure is rendered under the cursor
+            let figure = if current == Some(index) { None } else { system.get(index) };
+            item.render(surface, &self.texture, figure, palette, empty_field_color, bg_color)?;
         }
-        figures
-    }
-
-    pub fn destroy(self) -> Vec<Basket> {
-        self.basket
+        Ok(())
     }
 
-    pub fn render(
-        &self, surface: &mut Canvas<Window>, empty_field_color: Color, bg_color: Color,
+    // figures which are not in the basket system yet
+    pub fn render_figures(
+        &self, surface: &mut Canvas<Window>, figures: &[Figure], palette: &[Color], empty_field_color: Color,
+        bg_color: Color,
     ) -> Result<(), String> {
-        for item in &self.basket {
-            item.render(surface, &self.texture, empty_field_color, bg_color)?;
+        for (index, item) in self.basket.iter().enumerate() {
+            item.render(surface, &self.texture, figures.get(index), palette, empty_field_color, bg_color)?;
         }
         Ok(())
     }
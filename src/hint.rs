@@ -0,0 +1,166 @@
+use crate::ai::{Greedy, Strategy};
+use crate::coord::Coord;
+use crate::engine::{BasketSystem, Field, Figure, GameEngine, Lines};
+
+// heuristic weights
+const LINE_WEIGHT: i32 = 50;
+const SPACE_WEIGHT: i32 = 1;
+const HOLE_WEIGHT: i32 = 8;
+const FIT_WEIGHT: i32 = 1;
+// figures with this blocks count are hard to fit
+const LARGE_FIGURE_BLOCKS: u32 = 5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Placement {
+    pub basket: usize,
+    pub cell: Coord,
+    // clockwise turns before the figure is set
+    pub turns: u8,
+}
+
+impl Placement {
+    // basket figure turned as it should be set
+    pub fn figure(&self, basket: &BasketSystem) -> Option<Figure> {
+        let figure = basket.get(self.basket)?;
+        Some((0..self.turns).fold(figure.clone(), |figure, _| figure.rotate()))
+    }
+}
+
+// figure orientations with clockwise turns to get them
+pub fn orientations(figure: &Figure, rotation: bool) -> Vec<(u8, Figure)> {
+    if !rotation {
+        return vec![(0, figure.clone())];
+    }
+    figure.rotations().into_iter().enumerate().map(|(turns, figure)| (turns as u8, figure)).collect()
+}
+
+// all cells where figure can be set
+pub fn cells<'a>(field: &'a Field, figure: &'a Figure) -> impl Iterator<Item = Coord> + 'a {
+    let last = field.field_size - figure.max();
+    (0..last.y)
+        .flat_map(move |y| (0..last.x).map(move |x| coord!(x, y)))
+        .filter(|&cell| field.can_set_figure(cell, figure))
+}
+
+// empty cells without empty neighbours
+fn holes(field: &Field) -> i32 {
+    let mut count = 0;
+    for y in 0..field.field_size.y {
+        for x in 0..field.field_size.x {
+            let pos = coord!(x, y);
+            if !field.is_free(&pos) {
+                continue;
+            }
+            let free = [coord!(x - 1, y), coord!(x + 1, y), coord!(x, y - 1), coord!(x, y + 1)]
+                .iter()
+                .any(|p| field.is_free(p));
+            if !free {
+                count += 1;
+            }
+        }
+    }
+    count
+}
+
+// rate field after the move, bigger is better
+pub fn rate(field: &Field, lines: Lines, large: &[Figure]) -> i32 {
+    let lines = (lines.x + lines.y) as i32;
+    let fits = large.iter().map(|figure| cells(field, figure).count()).sum::<usize>() as i32;
+    lines * LINE_WEIGHT + field.free_cells() as i32 * SPACE_WEIGHT - holes(field) * HOLE_WEIGHT + fits * FIT_WEIGHT
+}
+
+// figures which should fit after the move
+pub fn large_figures(figures: &[Figure]) -> Vec<Figure> {
+    figures.iter().filter(|figure| figure.blocks() >= LARGE_FIGURE_BLOCKS).cloned().collect()
+}
+
+// field after the figure is set at `cell` and lines are removed
+pub fn apply(field: &Field, cell: Coord, figure: &Figure) -> Option<(Field, Lines)> {
+    let mut field = field.clone();
+    if !field.set_figure(cell, figure) {
+        return None;
+    }
+    let lines = field.next_state().map(|(lines, _)| lines).unwrap_or(Lines::empty());
+    Some((field, lines))
+}
+
+// best placement of the basket figures
+pub fn best(engine: &GameEngine) -> Option<Placement> {
+    Greedy::new(engine.figures(), engine.rules().rotation).choose(engine.field(), engine.basket())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::{HOLD_INDEX, Rules, default_figures};
+    use crate::figure;
+
+    #[test]
+    fn prefer_line_clear() {
+        let mut engine = GameEngine::new(default_figures(), 1010);
+        // one cell is left in the first row
+        for x in 1..10 {
+            engine.field_mut().set(coord!(x, 0), 0);
+        }
+        let single = figure!(99, 0; (0, 0));
+        engine.basket_mut().set(0, single);
+
+        let hint = best(&engine).unwrap();
+        assert_eq!(hint, Placement { basket: 0, cell: coord!(0, 0), turns: 0 });
+        assert!(engine.place(hint.basket, hint.cell).is_ok());
+    }
+
+    #[test]
+    fn no_hint_at_game_over() {
+        let mut engine = GameEngine::new(default_figures(), 7);
+        for y in 0..10 {
+            for x in 0..10 {
+                if (x + y) % 2 == 0 {
+                    engine.field_mut().set(coord!(x, y), 0);
+                }
+            }
+        }
+        for index in 0..engine.basket().len() {
+            engine.basket_mut().set(index, figure!(99, 0; (0, 0), (1, 0)));
+        }
+        assert_eq!(best(&engine), None);
+    }
+
+    #[test]
+    fn hint_with_held_figure() {
+        let rules = Rules { hold: true, ..Rules::default() };
+        let mut engine = GameEngine::with_rules(default_figures(), 7, rules);
+        // only single blocks fit into a checkerboard
+        for y in 0..10 {
+            for x in 0..10 {
+                if (x + y) % 2 == 0 {
+                    engine.field_mut().set(coord!(x, y), 0);
+                }
+            }
+        }
+        engine.basket_mut().clear();
+        engine.basket_mut().set(0, figure!(99, 0; (0, 0), (1, 0)));
+        engine.basket_mut().set(HOLD_INDEX, figure!(98, 0; (0, 0)));
+        assert!(!engine.is_game_over());
+
+        let hint = best(&engine).unwrap();
+        assert_eq!(hint.basket, HOLD_INDEX);
+        assert!(engine.place(hint.basket, hint.cell).is_ok());
+    }
+
+    #[test]
+    fn hint_with_rotation() {
+        let rules = Rules { rotation: true, ..Rules::default() };
+        let mut engine = GameEngine::with_rules(default_figures(), 1010, rules);
+        // vertical line clears the first column
+        for y in 0..5 {
+            engine.field_mut().set(coord!(0, y), 0);
+        }
+        engine.basket_mut().clear();
+        engine.basket_mut().set(0, figure!(99, 0; (0, 0), (1, 0), (2, 0), (3, 0), (4, 0)));
+
+        let hint = best(&engine).unwrap();
+        assert_eq!(hint, Placement { basket: 0, cell: coord!(0, 5), turns: 1 });
+        assert_eq!(hint.figure(engine.basket()), Some(figure!(99, 0; (0, 0), (0, 1), (0, 2), (0, 3), (0, 4))));
+    }
+}
@@ -0,0 +1,191 @@
+use std::collections::VecDeque;
+use std::mem;
+
+use crate::engine::{Event, GameEngine};
+
+// undo stack size
+pub const UNDO_LIMIT: usize = 32;
+
+struct Snapshot {
+    engine: GameEngine,
+    // events of the move between snapshots
+    events: Vec<Event>,
+}
+
+pub struct History {
+    undo: VecDeque<Snapshot>,
+    redo: Vec<Snapshot>,
+    limit: usize,
+    used: bool,
+}
+
+impl History {
+    pub fn new(limit: usize) -> History {
+        History { undo: VecDeque::new(), redo: Vec::new(), limit, used: false }
+    }
+
+    // store game state before the move
+    pub fn push(&mut self, before: GameEngine, events: Vec<Event>) {
+        self.redo.clear();
+        self.undo.push_back(Snapshot { engine: before, events });
+        if self.undo.len() > self.limit {
+            self.undo.pop_front();
+        }
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    pub fn undo(&mut self, engine: &mut GameEngine) -> bool {
+        let Some(Snapshot { engine: before, events }) = self.undo.pop_back() else {
+            return false;
+        };
+        let after = mem::replace(engine, before);
+        self.redo.push(Snapshot { engine: after, events });
+        self.used = true;
+        true
+    }
+
+    // returns events of the repeated move
+    pub fn redo(&mut self, engine: &mut GameEngine) -> Option<Vec<Event>> {
+        let Snapshot { engine: after, events } = self.redo.pop()?;
+        let before = mem::replace(engine, after);
+        self.undo.push_back(Snapshot { engine: before, events: events.clone() });
+        Some(events)
+    }
+
+    // undo was used at least once in this game
+    pub fn is_used(&self) -> bool {
+        self.used
+    }
+
+    pub fn set_used(&mut self, used: bool) {
+        self.used = used;
+    }
+
+    // forced move, nothing before it can be undone
+    pub fn seal(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+    }
+
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+        self.used = false;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::{Rules, default_figures};
+
+    fn place_any(engine: &mut GameEngine, history: &mut History) {
+        for y in 0..10 {
+            for x in 0..10 {
+                let before = engine.clone();
+                if let Ok(events) = engine.place(0, coord!(x, y)) {
+                    history.push(before, events);
+                    return;
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn undo_redo() {
+        let mut engine = GameEngine::new(default_figures(), 1010);
+        let mut history = History::new(UNDO_LIMIT);
+        let start = engine.field().blocks();
+
+        place_any(&mut engine, &mut history);
+        let score = engine.score();
+        let blocks = engine.field().blocks();
+        assert!(!history.is_used());
+
+        assert!(history.undo(&mut engine));
+        assert_eq!(engine.score(), 0);
+        assert_eq!(engine.field().blocks(), start);
+        assert!(!history.undo(&mut engine));
+        assert!(history.is_used());
+
+        assert!(history.redo(&mut engine).is_some());
+        assert_eq!(engine.score(), score);
+        assert_eq!(engine.field().blocks(), blocks);
+        assert!(history.redo(&mut engine).is_none());
+    }
+
+    #[test]
+    fn sealed_skip() {
+        let mut engine = GameEngine::new(default_figures(), 1010);
+        let mut history = History::new(UNDO_LIMIT);
+        place_any(&mut engine, &mut history);
+        // blitz timeout throws the basket away
+        engine.skip();
+        history.seal();
+        let figures = engine.basket().figures();
+
+        assert!(!history.undo(&mut engine));
+        assert!(history.redo(&mut engine).is_none());
+        assert_eq!(engine.basket().figures(), figures);
+        assert!(!history.is_used());
+    }
+
+    #[test]
+    fn undo_keeps_random_sequence() {
+        let mut engine = GameEngine::new(default_figures(), 42);
+        let mut history = History::new(UNDO_LIMIT);
+        for _ in 0..3 {
+            engine.basket_mut().pop(1);
+            engine.basket_mut().pop(2);
+            place_any(&mut engine, &mut history);
+        }
+        let refilled = engine.basket().figures();
+
+        history.undo(&mut engine);
+        engine.basket_mut().pop(1);
+        engine.basket_mut().pop(2);
+        place_any(&mut engine, &mut history);
+        assert_eq!(engine.basket().figures(), refilled);
+        assert!(!history.can_redo());
+    }
+
+    #[test]
+    fn undo_hold() {
+        let rules = Rules { hold: true, ..Rules::default() };
+        let mut engine = GameEngine::with_rules(default_figures(), 5, rules);
+        let mut history = History::new(UNDO_LIMIT);
+        place_any(&mut engine, &mut history);
+        let placed = (engine.field().blocks(), engine.basket().figures(), engine.score());
+
+        let before = engine.clone();
+        let events = engine.hold(1).unwrap();
+        history.push(before, events);
+        assert!(engine.basket().get(1).is_none());
+
+        // only the hold is reverted, the placed figure stays
+        assert!(history.undo(&mut engine));
+        assert_eq!((engine.field().blocks(), engine.basket().figures(), engine.score()), placed);
+        assert!(history.redo(&mut engine).is_some());
+        assert!(engine.basket().get(1).is_none());
+        assert!(!history.can_redo());
+    }
+
+    #[test]
+    fn limit() {
+        let mut engine = GameEngine::new(default_figures(), 7);
+        let mut history = History::new(2);
+        for _ in 0..3 {
+            history.push(engine.clone(), Vec::new());
+        }
+        assert!(history.undo(&mut engine));
+        assert!(history.undo(&mut engine));
+        assert!(!history.undo(&mut engine));
+    }
+}
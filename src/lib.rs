@@ -0,0 +1,18 @@
+#[macro_use]
+pub mod coord;
+pub mod ai;
+pub mod bits;
+pub mod board;
+pub mod codec;
+pub mod daily;
+pub mod engine;
+pub mod figures;
+pub mod hint;
+pub mod history;
+pub mod policy;
+pub mod puzzle;
+pub mod random;
+pub mod replay;
+pub mod save;
+pub mod scoring;
+pub mod slots;
@@ -1,39 +1,63 @@
 #![windows_subsystem = "windows"]
+use std::fs;
 use std::panic;
+use std::path::Path;
+use std::time::SystemTime;
 
 use sdl2::controller::{Axis, Button};
 use sdl2::event::Event;
-use sdl2::keyboard::{Keycode, Scancode};
+use sdl2::keyboard::{Keycode, Mod, Scancode};
 use sdl2::mixer::{AUDIO_S16LSB, DEFAULT_CHANNELS, InitFlag};
 use sdl2::mouse::MouseButton;
 use sdl2::pixels::{Color, PixelFormatEnum};
 use sdl2::rect::Rect;
-use sdl2::render::BlendMode;
+use sdl2::render::{BlendMode, Canvas};
 use sdl2::surface::Surface;
+use sdl2::ttf::Font;
+use sdl2::video::Window;
 
 use tini::Ini;
 
+use rs_1010::ai;
+use rs_1010::board::BoardMask;
+use rs_1010::coord::Coord;
+use rs_1010::daily::Date;
+use rs_1010::engine::{self, BASKET_COUNT, GameEngine, HOLD_INDEX, Rules};
+use rs_1010::figures::FigureSet;
+use rs_1010::hint;
+use rs_1010::history::{History, UNDO_LIMIT};
+use rs_1010::policy::Policy;
+use rs_1010::puzzle::{LevelPack, Puzzle, Status};
+use rs_1010::replay::{Action, Player, Replay};
+use rs_1010::save;
+use rs_1010::scoring::Scoring;
+use rs_1010::slots::{self, Slot};
+
 use crate::consts::*;
 use crate::extra::v_as_color;
-use crate::game::{FPSLimiter, Figure, GameState, GameTime};
+use crate::game::{FPSLimiter, GameState, GameTime, MODES, Mode, SavedGame};
+
+#[macro_use]
+extern crate rs_1010;
 
 #[macro_use]
 mod extra;
 mod audio;
 mod build;
-mod codec;
+mod cli;
 mod consts;
 mod game;
 mod handler;
-mod random;
 mod render;
-mod save;
 mod score;
 
 fn main() {
     // handle panics
     panic::set_hook(Box::new(handler::panic_handler));
 
+    // command line options
+    let options = cli::Options::from_args();
+
     // load game config
     let mut config = match Ini::from_file(CONFIG_FILE) {
         Ok(value) => value,
@@ -44,25 +68,142 @@ fn main() {
     };
     let magnetization = config.get("game", "magnetization").unwrap_or(DEFAULT_MAGNET_PARAM);
     let alpha_value = config.get("game", "alpha").unwrap_or(DEFAULT_ALPHA_PARAM);
+    let hint_alpha = config.get("game", "hint_alpha").unwrap_or(DEFAULT_HINT_ALPHA);
     let cfg_user_name = config.get("game", "username").unwrap_or_else(|| DEFAULT_USER_NAME.to_string());
     let ask_username = config.get("game", "ask_username").unwrap_or_else(|| cfg_user_name == DEFAULT_USER_NAME);
     let show_fps = config.get("game", "show_fps").unwrap_or(DEFAULT_SHOW_FPS);
+    let show_preview = config.get("game", "show_preview").unwrap_or(DEFAULT_SHOW_PREVIEW);
+    // fixed seed gives the same figure sequence in every game
+    let cfg_seed = options.seed.or_else(|| config.get("game", "seed"));
+    let save_replay = config.get("game", "save_replay").unwrap_or(DEFAULT_SAVE_REPLAY);
+    let undo_enabled = config.get("game", "undo").unwrap_or(DEFAULT_UNDO);
+    let mark_undo = config.get("game", "mark_undo").unwrap_or(DEFAULT_MARK_UNDO);
+    let mut replay_speed = options.speed.unwrap_or(DEFAULT_REPLAY_SPEED).clamp(MIN_REPLAY_SPEED, MAX_REPLAY_SPEED);
+    // figure set from file or built-in one
+    let figure_set = match config.get::<String>("game", "figures") {
+        Some(file) => FigureSet::load(&file).unwrap_or_else(|err| {
+            eprintln!("[figures::warning] problem with load `{}`: {}", file, err);
+            FigureSet::default()
+        }),
+        None => FigureSet::default(),
+    };
+    // daily challenge is the same for everyone, it is played with the built-in figures
+    let daily_set = FigureSet::default();
+    // how the next figures are chosen
+    let cfg_policy = config.get::<String>("game", "random").map_or(Policy::default(), |name| {
+        name.parse().unwrap_or_else(|err| {
+            eprintln!("[game::warning] {}", err);
+            Policy::default()
+        })
+    });
+    // how the moves are scored
+    let cfg_scoring = config.get::<String>("game", "scoring").map_or(Scoring::default(), |name| {
+        name.parse().unwrap_or_else(|err| {
+            eprintln!("[game::warning] {}", err);
+            Scoring::default()
+        })
+    });
+    // field width and height
+    let cfg_board = config.get::<String>("game", "board").map_or(Rules::default().board, |value| {
+        engine::parse_board(&value).unwrap_or_else(|err| {
+            eprintln!("[game::warning] {}", err);
+            Rules::default().board
+        })
+    });
+    // board shape from file or rectangle of the board size
+    let cfg_mask = match config.get::<String>("game", "mask") {
+        Some(file) => BoardMask::load(&file).unwrap_or_else(|err| {
+            eprintln!("[board::warning] problem with load `{}`: {}", file, err);
+            BoardMask::new(cfg_board)
+        }),
+        None => BoardMask::new(cfg_board),
+    };
+    let cfg_rules = Rules {
+        policy: cfg_policy,
+        fair: config.get("game", "fair").unwrap_or(DEFAULT_FAIR),
+        rotation: config.get("game", "rotation").unwrap_or(DEFAULT_ROTATION),
+        hold: config.get("game", "hold").unwrap_or(DEFAULT_HOLD),
+        board: cfg_mask.size,
+        mask: cfg_mask.id(),
+        scoring: cfg_scoring,
+    };
+    // replay mode: play recorded game instead of user input
+    let mut replay_player = options.replay.and_then(|file| match Replay::load(&file) {
+        Ok(replay) if replay.figures != figure_set.id() => {
+            eprintln!("[replay::warning] `{}` is recorded with other figure set", file);
+            None
+        }
+        Ok(replay) if replay.rules.mask != 0 && replay.rules.mask != cfg_rules.mask => {
+            eprintln!("[replay::warning] `{}` is recorded on other board", file);
+            None
+        }
+        Ok(replay) => Some(Player::new(replay)),
+        Err(err) => {
+            eprintln!("[replay::warning] problem with load `{}`: {}", file, err);
+            None
+        }
+    });
+    // computer player instead of user input
+    let mut autoplay = options.ai.filter(|_| replay_player.is_none()).and_then(|name| {
+        let strategy = ai::strategy(&name, &figure_set.figures, cfg_rules.rotation);
+        if strategy.is_none() {
+            eprintln!("[ai::warning] unknown strategy `{}`, available: {}", name, ai::STRATEGIES.join(", "));
+        }
+        strategy
+    });
+    // nobody plays with mouse and keyboard
+    let spectator = replay_player.is_some() || autoplay.is_some();
+
+    // play the game without window
+    if options.headless {
+        let Some(strategy) = autoplay.as_mut() else {
+            eprintln!("[ai::warning] headless mode needs `--ai <strategy>`");
+            return;
+        };
+        let seed = cfg_seed.unwrap_or_else(time_seed);
+        let mut engine = GameEngine::with_board(figure_set.figures.clone(), seed, cfg_rules, &cfg_mask);
+        let stats = ai::play(&mut engine, strategy.as_mut());
+        println!(
+            "strategy: {}, seed: {}, board: {}x{}, random: {}, fair: {}, rotation: {}, score: {}, moves: {}, lines: {}",
+            strategy.name(),
+            engine.seed(),
+            cfg_rules.board.x,
+            cfg_rules.board.y,
+            engine.policy(),
+            engine.rules().fair,
+            engine.rules().rotation,
+            stats.score,
+            stats.moves,
+            stats.lines
+        );
+        return;
+    }
+
     let mut score_table = score::ScoreTable::from_config(&config);
+    // levels of the puzzle mode
+    let puzzle_file = config.get::<String>("game", "puzzle").unwrap_or_else(|| DEFAULT_PUZZLE_FILE.to_string());
+    let level_pack = match LevelPack::load(&puzzle_file) {
+        Ok(pack) => Some(pack),
+        Err(err) => {
+            eprintln!("[puzzle::warning] problem with load `{}`: {}", puzzle_file, err);
+            None
+        }
+    };
+    // solved levels are kept for every pack
+    let progress_key = level_pack.as_ref().map(|pack| progress_key(&pack.name)).unwrap_or_default();
+    let mut solved: Vec<usize> = config.get_vec("puzzle", &progress_key).unwrap_or_default();
 
     // objects positions
-    let basket_pos = coord!(FIELD_WIDTH as i16, FIELD_SHIFT_HEIGHT + 4 * FONT_HEIGHT);
-    let basket_shift = coord!(0, BASKET_HEIGHT as i16);
-    let field_pos = coord!(FIELD_SHIFT_WIDTH, FIELD_SHIFT_HEIGHT);
-    let score_pos = coord!(FIELD_WIDTH as i16 + 3, FIELD_SHIFT_HEIGHT - 3);
-    let highscore_pos = score_pos + coord!(0, FONT_HEIGHT - 1);
-    let timer_pos = highscore_pos + coord!(0, FONT_HEIGHT - 1);
-    let separator_pos = timer_pos + coord!(0, FONT_HEIGHT - 1);
+    let seed_pos = coord!(10, W_HEIGHT as i16 - 2 * FONT_MIN_SIZE as i16);
+    let replay_pos = seed_pos - coord!(0, FONT_MIN_SIZE as i16 + 4);
     let mut mouse_pos = coord!();
     let mut figure_pos = coord!();
 
     // SDL2
     let sdl_context = sdl2::init().expect(INIT_SDL_ERROR);
     let video_subsystem = sdl_context.video().unwrap_or_else(|_| subsystem_panic!(create; "video"));
+    // position codes are copied and pasted through the system clipboard
+    let clipboard = video_subsystem.clipboard();
     // init audio subsystem
     let _audio_subsystem = sdl_context.audio().unwrap_or_else(|_| subsystem_panic!(create; "audio"));
     sdl2::mixer::open_audio(44100, AUDIO_S16LSB, DEFAULT_CHANNELS, 1024)
@@ -135,91 +276,10 @@ fn main() {
         v_as_color(&config, "color", "light", FONT_BCOLOR),
         // 12: border_color
         v_as_color(&config, "color", "border", BORDER_COLOR),
+        // 13: blocked_cell_color
+        v_as_color(&config, "color", "blocked", BLOCKED_COLOR),
     ];
 
-    // available game figures
-    let figures = &[
-        // ###
-        // ###
-        // ###
-        figure!(1, palette[0]; (0, 0), (1, 0), (2, 0), (0, 1), (1, 1), (2, 1), (0, 2), (1, 2), (2, 2)),
-        // ##
-        // ##
-        figure!(2, palette[0]; (0, 0), (1, 0), (0, 1), (1, 1)),
-        // #
-        figure!(3, palette[0]; (0, 0)),
-        // #####
-        figure!(4, palette[1]; (0, 0), (0, 1), (0, 2), (0, 3), (0, 4)),
-        // #
-        // #
-        // #
-        // #
-        // #
-        figure!(5, palette[1]; (0, 0), (1, 0), (2, 0), (3, 0), (4, 0)),
-        // #
-        // #
-        // #
-        // #
-        figure!(6, palette[2]; (0, 0), (0, 1), (0, 2), (0, 3)),
-        // ####
-        figure!(7, palette[2]; (0, 0), (1, 0), (2, 0), (3, 0)),
-        // #
-        // #
-        // #
-        figure!(8, palette[3]; (0, 0), (0, 1), (0, 2)),
-        // ###
-        figure!(9, palette[3]; (0, 0), (1, 0), (2, 0)),
-        // #
-        // #
-        figure!(10, palette[4]; (0, 0), (0, 1)),
-        // ##
-        figure!(11, palette[4]; (0, 0), (1, 0)),
-        // ###
-        //   #
-        //   #
-        figure!(12, palette[5]; (0, 0), (1, 0), (2, 0), (2, 1), (2, 2)),
-        //   #
-        //   #
-        // ###
-        figure!(13, palette[5]; (2, 0), (2, 1), (0, 2), (1, 2), (2, 2)),
-        // #
-        // #
-        // ###
-        figure!(14, palette[5]; (0, 0), (0, 1), (0, 2), (1, 2), (2, 2)),
-        // ###
-        // #
-        // #
-        figure!(15, palette[5]; (0, 0), (1, 0), (2, 0), (0, 1), (0, 2)),
-        // ###
-        //   #
-        figure!(16, palette[6]; (0, 0), (1, 0), (2, 0), (2, 1)),
-        //   #
-        // ###
-        figure!(17, palette[6]; (1, 0), (1, 1), (0, 2), (1, 2)),
-        // #
-        // ###
-        figure!(18, palette[6]; (0, 0), (0, 1), (1, 1), (2, 1)),
-        // ##
-        // #
-        // #
-        figure!(19, palette[6]; (0, 0), (1, 0), (0, 1), (0, 2)),
-        // ##
-        //  #
-        figure!(20, palette[7]; (0, 0), (1, 0), (1, 1)),
-        //  #
-        // ##
-        figure!(21, palette[7]; (1, 0), (0, 1), (1, 1)),
-        // #
-        // ##
-        figure!(22, palette[7]; (0, 0), (0, 1), (1, 1)),
-        // ##
-        // #
-        figure!(23, palette[7]; (0, 0), (1, 0), (0, 1)),
-    ];
-
-    // game scores
-    let mut highscore = score_table.get_highscore();
-    let mut score: u32 = 0;
     // for username input
     let mut user_name = String::new();
     // rendering params
@@ -231,18 +291,25 @@ fn main() {
     }
 
     // game objects
-    let mut current_figure: Option<game::Figure> = None;
-    let mut field = game::Field::init_square(FIELD_LEN, TILE_SIZE_1, TILE_SEP_1, ROUND_STEPS, ROUND_RADIUS, field_pos);
-    let mut basket = game::BasketSystem::new(
-        BASKET_COUNT,
-        BASKET_SIZE,
-        TILE_SIZE_2,
-        TILE_SEP_2,
-        BASKET_ROUND_STEPS,
-        ROUND_RADIUS,
-        basket_pos,
-        basket_shift,
-    );
+    let mut current_figure: Option<usize> = None;
+    let (seed, rules) = match &replay_player {
+        Some(player) => (player.seed(), player.rules()),
+        None => (cfg_seed.unwrap_or_else(time_seed), cfg_rules),
+    };
+    // replays are played on their own board, the mask of the config is used only if it was recorded on it
+    let board = match &replay_player {
+        Some(player) => player.board(&cfg_mask),
+        None => cfg_mask.clone(),
+    };
+    let mut engine = GameEngine::with_board(figure_set.figures.clone(), seed, rules, &board);
+    if !spectator {
+        config = migrate_saves(config, &engine);
+    }
+    // same limit for all games, so replays undo the same moves
+    let mut history = History::new(UNDO_LIMIT);
+    // suggested move and hints count in this game
+    let mut hint = None;
+    let mut hint_count = 0;
 
     // font rendering surface
     let surface_size = Rect::new(0, 0, W_WIDTH, W_HEIGHT);
@@ -250,26 +317,57 @@ fn main() {
     msg!(surface.set_blend_mode(BlendMode::Blend); canvas.window(), GT);
     let surface_bg = Color::RGBA(palette[8].r, palette[8].g, palette[8].b, 0);
 
-    // fill basket by random figures
-    basket.rnd_fill(figures);
-
     let fps = config.get("game", "fps").unwrap_or(DEFAULT_FPS_PARAM);
     let mut fps_limiter = FPSLimiter::new(fps, timer.ticks());
     let mut game_time = GameTime::new();
 
-    // restore game state
-    if let Some(state) = config.get::<String>("game", "state") {
-        // deserialize
-        save::deserialize(state, &palette, figures, &mut field, &mut basket, &mut score, &mut game_time);
+    // game moves for replay file
+    let mut recorder = None;
+    let mut last_ticks = timer.ticks();
+    // planned move of computer player
+    let mut ai_move = None;
+    let mut ai_clock = 0.0;
+
+    // players choose the mode at start, replays and computer games are endless
+    let mut mode = Mode::Endless;
+    let mut mode_cursor = 0;
+    let show_highscore = config.get("game", "show_highscore_at_start").unwrap_or(DEFAULT_HIGHSCORE_AT_START);
+    let blitz_time = config.get("game", "blitz_time").unwrap_or(DEFAULT_BLITZ_TIME).max(1) * MILLISECOND;
+    // game time of the last move for the blitz timer
+    let mut move_start = 0;
+    if autoplay.is_some() {
+        recorder = Some(Replay::new(engine.seed(), figure_set.id(), engine.rules()));
     }
+    let mut highscore = score_table.get_highscore(engine.rules(), mode);
+    // results of the daily challenge and the day of the played one
+    let mut daily_history = score::DailyHistory::from_config(&config);
+    let mut daily_date = Date::today();
+    // objects positions depend on the board size
+    let mut game_board = board.clone();
+    let (mut layout, mut field, mut basket, mut preview) = build_views(&game_board, engine.rules().hold);
+
+    // puzzle mode: played level with its progress and the endless game
+    let mut puzzle: Option<(usize, Puzzle)> = None;
+    let mut saved_game: Option<SavedGame> = None;
+    let mut level_cursor = 0;
+    let mut level_title = LEVEL_SELECT;
+    // state of the endless game before the level select
+    let mut back_state = GameState::Idle;
+
+    // save slots: files of the picker, the last finished game and the state before the picker
+    let mut slot_list: Vec<Slot> = Vec::new();
+    let mut slot_cursor = 0;
+    let mut slot_back = GameState::Idle;
+    let mut finished: Option<Slot> = None;
+    // slot waiting for the name
+    let mut slot_draft: Option<Slot> = None;
+    // loaded snapshot of the finished game is only viewed, it is not scored or saved
+    let mut analysis = false;
+    // game from the pasted position code does not go to the leaderboard or the autosave
+    let mut practice = false;
 
     // game stuff
-    let mut game_state =
-        if config.get("game", "show_highscore_at_start").unwrap_or(DEFAULT_HIGHSCORE_AT_START) && field.is_empty() {
-            GameState::HighscoreTable
-        } else {
-            GameState::Idle
-        };
+    let mut game_state = if spectator { GameState::Idle } else { GameState::ModeSelect };
 
     let mut event_pump = msg!(sdl_context.event_pump(); canvas.window(), GT);
     'running: loop {
@@ -287,38 +385,72 @@ fn main() {
 
         if game_state != GameState::Pause {
             // field and basket
-            msg!(field.render(&mut canvas, palette[9], palette[8]); canvas.window(), GT);
-            msg!(basket.render(&mut canvas, palette[9], palette[8]); canvas.window(), GT);
+            msg!(field.render(&mut canvas, engine.field(), &palette, palette[9], palette[13], palette[8]); canvas.window(), GT);
+            msg!(basket.render(&mut canvas, engine.basket(), current_figure, &palette, palette[9], palette[8]); canvas.window(), GT);
+            if show_preview {
+                msg!(preview.render_figures(&mut canvas, engine.basket().next(), &palette, palette[9], palette[8]); canvas.window(), GT);
+            }
 
             // score, highscore and timer
-            msg!(render::font(&mut surface, &font, score_pos, palette[10], palette[8], &format!("{:08}", score)); canvas.window(), GT);
-            msg!(render::font(&mut surface, &font, highscore_pos, palette[10], palette[8], &format!("{:08}", highscore)); canvas.window(), GT);
-            msg!(render::font(&mut surface, &font, timer_pos, palette[10], palette[8], &game_time.format()); canvas.window(), GT);
-            msg!(render::font(&mut surface, &font, separator_pos, palette[10], palette[8], "————————"); canvas.window(), GT);
+            msg!(field.render_labels(&mut surface, &font_min, palette[10], surface_bg); canvas.window(), GT);
+            msg!(render::font(&mut surface, &font, layout.score, palette[10], palette[8], &format!("{:08}", engine.score())); canvas.window(), GT);
+            // level goal and moves left are shown instead of the highscore
+            let (goal, moves, footer) = match (&puzzle, &level_pack) {
+                (Some((index, state)), Some(pack)) => {
+                    let (goal, moves) = state.progress(&engine);
+                    (goal, moves.unwrap_or_else(|| game_time.format()), format!("level: {}", pack.levels[*index].name))
+                }
+                _ if mode == Mode::Daily => (
+                    format!("{:08}", highscore),
+                    mode_clock(mode, &game_time, move_start, blitz_time),
+                    format!("daily: {}, {}: {}", daily_date, DAILY_STREAK, daily_history.streak(Date::today())),
+                ),
+                _ if practice => (
+                    format!("{:08}", highscore),
+                    mode_clock(mode, &game_time, move_start, blitz_time),
+                    PRACTICE_GAME.to_string(),
+                ),
+                _ => (
+                    format!("{:08}", highscore),
+                    mode_clock(mode, &game_time, move_start, blitz_time),
+                    format!("seed: {}", engine.seed()),
+                ),
+            };
+            msg!(render::font(&mut surface, &font, layout.highscore, palette[10], palette[8], &goal); canvas.window(), GT);
+            msg!(render::font(&mut surface, &font, layout.timer, palette[10], palette[8], &moves); canvas.window(), GT);
+            msg!(render::font(&mut surface, &font, layout.separator, palette[10], palette[8], "————————"); canvas.window(), GT);
+            msg!(render::font(&mut surface, &font_min, seed_pos, palette[10], palette[8], &footer); canvas.window(), GT);
+            if let Some(player) = &replay_player {
+                let status = if player.is_finished() { "finished".to_string() } else { format!("x{}", replay_speed) };
+                msg!(render::font(&mut surface, &font_min, replay_pos, palette[10], palette[8], &format!("replay: {}", status)); canvas.window(), GT);
+            }
+            if let Some(strategy) = &autoplay {
+                let status = format!("ai: {} x{}", strategy.name(), replay_speed);
+                msg!(render::font(&mut surface, &font_min, replay_pos, palette[10], palette[8], &status); canvas.window(), GT);
+            }
         }
 
         if show_fps {
             msg!(render::font(&mut surface, &font, coord!(10), palette[10], palette[8], &format!("{fps}")); canvas.window(), GT);
         }
 
-        // remove last game state
-        if game_state == GameState::GameOver && config.get::<String>("game", "state").is_some() {
-            // remove state from config (ignore errors)
-            config = config.section("game").erase("state");
-            let _ = config.to_file(CONFIG_FILE);
-        }
-
         // show highscore table
         if game_state == GameState::HighscoreTable || game_state == GameState::GameOver {
             // highscore table
             let mut scores = Vec::new();
-            let mut ss = coord!();
             let mut curr_score = None;
-            let mut max_score_width = fsx;
-
-            for (index, score::Score { name, score, time, last }) in
-                score_table.iter().take(GAMESCORE_COUNT).enumerate()
-            {
+            // daily challenge shows its history instead of the leaderboard
+            if mode == Mode::Daily {
+                scores.push(format!("{}: {}", DAILY_STREAK, daily_history.streak(Date::today())));
+                for &(date, score) in daily_history.iter().take(GAMESCORE_COUNT) {
+                    if date == daily_date {
+                        curr_score = Some(scores.len());
+                    }
+                    scores.push(format!("{} {:08}", date, score));
+                }
+            }
+            for (index, item) in score_table.iter().filter(|item| item.mode == mode).take(GAMESCORE_COUNT).enumerate() {
+                let score::Score { name, score, time, last, .. } = item;
                 let name = if name.chars().count() > MAX_NAME_SIZE {
                     format!("{}...", &name[..MAX_NAME_SIZE - 3])
                 } else {
@@ -327,35 +459,68 @@ fn main() {
                 if *last {
                     curr_score = Some(index);
                 }
-                let score = format!("{}. {: <4$} {:08} ({})", index + 1, name, score, time, MAX_NAME_SIZE);
-                let (ssx, ssy) = msg!(font_min.size_of(&score); canvas.window(), GT);
-                ss.y += ssy as i16;
-                ss.x = ss.x.max(ssx as i16);
-                max_score_width = max_score_width.max(font_min.size_of(&score).unwrap_or((0, 0)).0);
+                let score =
+                    format!("{}. {: <5$} {:08}{:1} ({})", index + 1, name, score, item.marks(), time, MAX_NAME_SIZE);
                 scores.push(score);
             }
+            msg!(render_table(&mut canvas, &mut surface, &font_big, &font_min, GAME_OVER, &scores, curr_score, &palette); canvas.window(), GT);
+        }
+
+        // mode select at start
+        if game_state == GameState::ModeSelect {
+            let today = daily_history.get(Date::today());
+            let mut modes: Vec<String> = MODES
+                .iter()
+                .map(|mode| match today {
+                    Some(score) if *mode == Mode::Daily => format!("{} ({}: {})", mode.title(), DAILY_DONE, score),
+                    _ => mode.title(),
+                })
+                .collect();
+            if level_pack.is_some() {
+                modes.push(PUZZLE_MODE.to_string());
+            }
+            msg!(render_table(&mut canvas, &mut surface, &font_big, &font_min, MODE_SELECT, &modes, Some(mode_cursor), &palette); canvas.window(), GT);
+        }
 
-            let fp1 =
-                coord!((W_WIDTH as i16 - max_score_width as i16) >> 1, (W_HEIGHT as i16 - fsy as i16 - ss.y) >> 1);
-            let p1 = fp1 - 2 * BORDER;
-            let p2 = fp1 + coord!(max_score_width as i16, ss.y + fsy as i16 - BORDER) + 2 * BORDER;
-            let p3 = p1 + BORDER;
-            let p4 = p2 - BORDER;
+        // level select of the puzzle mode
+        if game_state == GameState::LevelSelect
+            && let Some(pack) = &level_pack
+        {
+            let mut levels: Vec<String> = pack
+                .levels
+                .iter()
+                .enumerate()
+                .map(|(index, level)| {
+                    let mark = if solved.contains(&index) { LEVEL_DONE_MARK } else { "" };
+                    format!("{}. {: <3$} {}", index + 1, level.name, mark, MAX_NAME_SIZE)
+                })
+                .collect();
+            levels.push(ENDLESS_GAME.to_string());
+            msg!(render_table(&mut canvas, &mut surface, &font_big, &font_min, level_title, &levels, Some(level_cursor), &palette); canvas.window(), GT);
+        }
 
-            msg!(render::fill_rect(&mut canvas, p1, p2, palette[12]); canvas.window(), GT);
-            msg!(render::fill_rect(&mut canvas, p3, p4, palette[8]); canvas.window(), GT);
-            msg!(render::font(&mut surface, &font_big, fp1 - coord!(-10, 5), palette[10], palette[8], GAME_OVER); canvas.window(), GT);
-            for (index, text) in scores.iter().enumerate() {
-                let fp2 = fp1 + coord!(0, fsy as i16 + index as i16 * (ss.y / scores.len() as i16)) - coord!(0, BORDER);
-                let fcolor = if Some(index) == curr_score { palette[11] } else { palette[10] };
-                msg!(render::font(&mut surface, &font_min, fp2, fcolor, palette[8], text); canvas.window(), GT);
+        // save slots
+        if game_state == GameState::SlotSelect {
+            let mut rows = vec![SLOT_SAVE.to_string()];
+            for slot in &slot_list {
+                let mark = if slot.over { SLOT_OVER_MARK } else { "" };
+                let time = game::format_seconds(slot.elapsed);
+                rows.push(format!(
+                    "{: <6$} {: <7} {:08} {} {} {}",
+                    slot.name, slot.mode, slot.score, time, slot.date, mark, MAX_NAME_SIZE
+                ));
             }
+            rows.push(SLOT_BACK.to_string());
+            msg!(render_table(&mut canvas, &mut surface, &font_big, &font_min, SLOT_SELECT, &rows, Some(slot_cursor), &palette); canvas.window(), GT);
         }
 
-        // input username
-        if game_state == GameState::UsernameInput {
-            // gameover input name
-            let input_name = format!("{}{}", GAME_OVER_TEXT, user_name);
+        // input username or slot name
+        if matches!(game_state, GameState::UsernameInput | GameState::SlotName) {
+            let (title, prompt) = match game_state {
+                GameState::SlotName => (SLOT_SELECT, SLOT_NAME_TEXT),
+                _ => (GAME_OVER, GAME_OVER_TEXT),
+            };
+            let input_name = format!("{}{}", prompt, user_name);
 
             // prepare textures for input name form
             let inf_ssy = (3 * FONT_MIN_SIZE) as i16;
@@ -368,7 +533,7 @@ fn main() {
 
             msg!(render::fill_rect(&mut canvas, p1, p2, palette[12]); canvas.window(), GT);
             msg!(render::fill_rect(&mut canvas, p3, p4, palette[8]); canvas.window(), GT);
-            msg!(render::font(&mut surface, &font_big, inf_fp1, palette[10], palette[8], GAME_OVER); canvas.window(), GT);
+            msg!(render::font(&mut surface, &font_big, inf_fp1, palette[10], palette[8], title); canvas.window(), GT);
             msg!(render::font(&mut surface, &font, inf_fp2, palette[10], palette[8], &input_name); canvas.window(), GT);
         }
 
@@ -384,23 +549,119 @@ fn main() {
             game_time.tick();
         }
 
+        // blitz mode: basket figures are lost when the move takes too long
+        if mode == Mode::Blitz
+            && puzzle.is_none()
+            && !analysis
+            && game_state == GameState::Idle
+            && !field.is_animated()
+            && !engine.is_game_over()
+            && game_time.elapsed_millis().saturating_sub(move_start) >= blitz_time
+        {
+            revert_figure(&mut current_figure, &mut recorder, &game_time, &audio);
+            record(&mut recorder, &game_time, Action::Skip);
+            skip_figures(&mut engine, &mut history, &mut field, &audio);
+            move_start = game_time.elapsed_millis();
+            hint = None;
+        }
+
+        // play recorded moves
+        let ticks = timer.ticks();
+        if let Some(player) = replay_player.as_mut().filter(|_| game_state == GameState::Idle) {
+            for action in player.advance(ticks - last_ticks, replay_speed) {
+                current_figure = match action {
+                    Action::Pick { basket } => Some(basket as usize),
+                    Action::Place { basket, cell } => {
+                        audio.play_sfx(SFX_CLACK_ID);
+                        // replay doesn't wait for animation to get the same result
+                        place_figure(&mut engine, &mut history, basket as usize, cell, &mut field, &audio);
+                        None
+                    }
+                    Action::Undo | Action::Redo => {
+                        apply_history(action, &mut engine, &mut history, &mut field, &audio);
+                        None
+                    }
+                    Action::Rotate { basket } => {
+                        engine.rotate(basket as usize);
+                        current_figure
+                    }
+                    Action::Hold { basket } => {
+                        audio.play_sfx(SFX_CLACK_ID);
+                        hold_figure(&mut engine, &mut history, basket as usize, &mut field, &audio);
+                        None
+                    }
+                    Action::Skip => {
+                        skip_figures(&mut engine, &mut history, &mut field, &audio);
+                        None
+                    }
+                    Action::Revert => None,
+                };
+            }
+        }
+
+        // computer player moves
+        if let Some(strategy) = autoplay.as_mut().filter(|_| game_state == GameState::Idle && !field.is_animated()) {
+            ai_clock += (ticks - last_ticks) as f64 * replay_speed;
+            if ai_clock >= AI_MOVE_DELAY {
+                ai_clock = 0.0;
+                match ai_move.take() {
+                    // release the figure
+                    Some(hint::Placement { basket, cell, .. }) => {
+                        audio.play_sfx(SFX_CLACK_ID);
+                        record(&mut recorder, &game_time, Action::Place { basket: basket as u8, cell });
+                        place_figure(&mut engine, &mut history, basket, cell, &mut field, &audio);
+                        current_figure = None;
+                    }
+                    // park the figure which fits nowhere
+                    None if let Some(index) = ai::park(&engine) => {
+                        record(&mut recorder, &game_time, Action::Hold { basket: index as u8 });
+                        hold_figure(&mut engine, &mut history, index, &mut field, &audio);
+                    }
+                    // take the figure
+                    None => {
+                        ai_move = strategy.choose(engine.field(), engine.basket());
+                        if let Some(hint::Placement { basket, turns, .. }) = ai_move.as_mut() {
+                            audio.play_sfx(SFX_CLICK_ID);
+                            record(&mut recorder, &game_time, Action::Pick { basket: *basket as u8 });
+                            // turn the figure while it is held
+                            for _ in 0..std::mem::take(turns) {
+                                engine.rotate(*basket);
+                                record(&mut recorder, &game_time, Action::Rotate { basket: *basket as u8 });
+                            }
+                            current_figure = Some(*basket);
+                        }
+                    }
+                }
+            }
+        }
+        last_ticks = ticks;
+
         // events
+        let mut history_action = None;
         for event in event_pump.poll_iter() {
             match event {
+                // slot is not saved without the name
+                Event::KeyDown { keycode: Some(Keycode::Escape), .. } if game_state == GameState::SlotName => {
+                    slot_draft = None;
+                    user_name.clear();
+                    game_state = GameState::SlotSelect;
+                }
+
                 // exit the game
                 Event::Quit { .. }
                 | Event::KeyDown { keycode: Some(Keycode::Escape), .. }
                 | Event::ControllerButtonDown { button: Button::Back, .. } => break 'running,
 
                 // add user name to score table
-                Event::TextInput { text, .. } => {
-                    if game_state == GameState::UsernameInput && user_name.chars().count() < MAX_NAME_SIZE {
-                        user_name.push_str(&text);
-                    }
+                Event::TextInput { text, .. }
+                    if matches!(game_state, GameState::UsernameInput | GameState::SlotName)
+                        && user_name.chars().count() < MAX_NAME_SIZE =>
+                {
+                    user_name.push_str(&text);
                 }
 
                 // input user name
-                Event::KeyDown { scancode: Some(key), .. } => {
+                Event::KeyDown { scancode: Some(key), keymod, .. } => {
                     if game_state == GameState::UsernameInput {
                         match key {
                             Scancode::Return | Scancode::KpEnter => {
@@ -409,11 +670,39 @@ fn main() {
                                 if fixed_user_name.chars().count() == 0 {
                                     continue;
                                 }
-                                score_table.push(fixed_user_name, score, game_time.format());
+                                score_table.push(
+                                    fixed_user_name,
+                                    engine.score(),
+                                    game_time.format(),
+                                    game_assists(&history, hint_count, mark_undo),
+                                    engine.rules(),
+                                    mode,
+                                );
                                 user_name.clear();
                                 game_state = GameState::GameOver;
-                                field.clear();
-                                basket.clear();
+                                field.clear(engine.clear());
+                            }
+                            Scancode::Backspace => {
+                                user_name.pop();
+                            }
+                            _ => (),
+                        }
+                    } else if game_state == GameState::SlotName {
+                        match key {
+                            Scancode::Return | Scancode::KpEnter => {
+                                let name = user_name.trim().to_string();
+                                if name.is_empty() {
+                                    continue;
+                                }
+                                if let Some(mut slot) = slot_draft.take() {
+                                    slot.name = name;
+                                    write_slot(&slot);
+                                    slot_list = read_slots();
+                                }
+                                user_name.clear();
+                                game_state = GameState::SlotSelect;
+                                // the same key does not save the next slot
+                                continue;
                             }
                             Scancode::Backspace => {
                                 user_name.pop();
@@ -422,8 +711,327 @@ fn main() {
                         }
                     }
 
+                    // undo & redo, copy & paste position code
+                    if keymod.intersects(Mod::LCTRLMOD | Mod::RCTRLMOD) {
+                        match key {
+                            Scancode::Z => history_action = Some(Action::Undo),
+                            Scancode::Y => history_action = Some(Action::Redo),
+                            Scancode::C if game_state == GameState::Idle => {
+                                match clipboard.set_clipboard_text(&save::share_code(&engine)) {
+                                    Ok(()) => audio.play_sfx(SFX_CLICK_ID),
+                                    Err(err) => eprintln!("[save::warning] problem with copy: {}", err),
+                                }
+                            }
+                            Scancode::V
+                                if !spectator
+                                    && puzzle.is_none()
+                                    && matches!(
+                                        game_state,
+                                        GameState::Idle | GameState::HighscoreTable | GameState::GameOver
+                                    ) =>
+                            {
+                                let code = clipboard.clipboard_text().unwrap_or_default();
+                                let mut practice_engine =
+                                    GameEngine::with_board(figure_set.figures.clone(), time_seed(), cfg_rules, &board);
+                                if let Err(err) = save::load_code(&code, &mut practice_engine) {
+                                    eprintln!("[save::warning] pasted code: {}", err);
+                                    continue;
+                                }
+                                revert_figure(&mut current_figure, &mut recorder, &game_time, &audio);
+                                if game_state == GameState::Idle && engine.score() > 0 && !analysis && !practice {
+                                    autosave(mode, &engine, &game_time, game_assists(&history, hint_count, mark_undo));
+                                }
+                                if game_state == GameState::GameOver {
+                                    audio.play_music(MUSIC_BG_ID, audio::MusicLoop::Repeat);
+                                }
+                                // practice is played by the endless rules on the board of the config
+                                engine = practice_engine;
+                                mode = Mode::Endless;
+                                game_board = board.clone();
+                                practice = true;
+                                analysis = false;
+                                history.clear();
+                                game_time = GameTime::new();
+                                hint = None;
+                                hint_count = 0;
+                                recorder = None;
+                                highscore = score_table.get_highscore(engine.rules(), mode);
+                                (layout, field, basket, preview) = build_views(&game_board, engine.rules().hold);
+                                move_start = 0;
+                                game_state = GameState::Idle;
+                            }
+                            _ => (),
+                        }
+                    }
+
+                    // show best move
+                    if key == Scancode::H
+                        && game_state == GameState::Idle
+                        && !spectator
+                        && mode != Mode::Daily
+                        && hint.is_none()
+                    {
+                        hint = hint::best(&engine);
+                        hint_count += hint.is_some() as u32;
+                    }
+
+                    // choose the game mode
+                    if game_state == GameState::ModeSelect {
+                        let count = MODES.len() + level_pack.is_some() as usize;
+                        match key {
+                            Scancode::Up => mode_cursor = (mode_cursor + count - 1) % count,
+                            Scancode::Down => mode_cursor = (mode_cursor + 1) % count,
+                            Scancode::Return | Scancode::KpEnter if mode_cursor < MODES.len() => {
+                                mode = MODES[mode_cursor];
+                                daily_date = Date::today();
+                                // daily challenge has the seed of the day and the classic rules
+                                let (seed, rules) = match mode {
+                                    Mode::Daily => (daily_date.seed(), Rules::default()),
+                                    _ => (cfg_seed.unwrap_or_else(time_seed), cfg_rules),
+                                };
+                                game_board =
+                                    if mode == Mode::Daily { BoardMask::new(rules.board) } else { board.clone() };
+                                let set = if mode == Mode::Daily { &daily_set } else { &figure_set };
+                                let new_game = GameEngine::with_board(set.figures.clone(), seed, rules, &game_board);
+                                // mode continues its saved game or starts a new one
+                                engine = new_game.clone();
+                                hint = None;
+                                recorder = None;
+                                analysis = false;
+                                practice = false;
+                                let restored =
+                                    Slot::load(Path::new(SAVE_DIR).join(Slot::file_name(&autosave_name(mode))))
+                                        .is_ok_and(|slot| {
+                                            restore_game(
+                                                &slot,
+                                                &mut engine,
+                                                &mut game_time,
+                                                &mut history,
+                                                &mut hint_count,
+                                            )
+                                        });
+                                // saved daily game of the other day is not continued
+                                if !restored || mode == Mode::Daily && engine.seed() != seed {
+                                    engine = new_game;
+                                    history.clear();
+                                    game_time = GameTime::new();
+                                    hint_count = 0;
+                                    recorder = Some(Replay::new(engine.seed(), set.id(), engine.rules()));
+                                }
+                                // saved game may have other rules, daily challenge is compared with the day result
+                                highscore = match mode {
+                                    Mode::Daily => daily_history.get(daily_date).unwrap_or(0),
+                                    _ => score_table.get_highscore(engine.rules(), mode),
+                                };
+                                (layout, field, basket, preview) = build_views(&game_board, engine.rules().hold);
+                                move_start = game_time.elapsed_millis();
+                                game_state = if show_highscore && engine.field().is_empty() {
+                                    GameState::HighscoreTable
+                                } else {
+                                    GameState::Idle
+                                };
+                            }
+                            Scancode::Return | Scancode::KpEnter => {
+                                back_state = GameState::ModeSelect;
+                                level_title = LEVEL_SELECT;
+                                game_state = GameState::LevelSelect;
+                            }
+                            _ => (),
+                        }
+                    } else if key == Scancode::M
+                        && !spectator
+                        && puzzle.is_none()
+                        && matches!(game_state, GameState::Idle | GameState::HighscoreTable | GameState::GameOver)
+                    {
+                        revert_figure(&mut current_figure, &mut recorder, &game_time, &audio);
+                        game_time.pause();
+                        // unfinished game waits in the autosave slot of its mode
+                        if game_state == GameState::Idle && engine.score() > 0 && !analysis && !practice {
+                            autosave(mode, &engine, &game_time, game_assists(&history, hint_count, mark_undo));
+                        }
+                        game_state = GameState::ModeSelect;
+                    }
+
+                    // save the game to a new slot, load or delete the saved one
+                    if game_state == GameState::SlotSelect {
+                        let count = slot_list.len() + 2;
+                        match key {
+                            Scancode::Up => slot_cursor = (slot_cursor + count - 1) % count,
+                            Scancode::Down => slot_cursor = (slot_cursor + 1) % count,
+                            Scancode::Return | Scancode::KpEnter if slot_cursor == 0 => {
+                                let assists = game_assists(&history, hint_count, mark_undo);
+                                // finished game is kept as it was before the field was cleared
+                                let slot = match slot_back {
+                                    GameState::GameOver => finished.clone(),
+                                    _ => Some(game_slot(
+                                        String::new(),
+                                        mode,
+                                        &engine,
+                                        &game_time,
+                                        assists,
+                                        analysis || practice,
+                                    )),
+                                };
+                                // the first free number is offered as the name
+                                if slot.is_some() {
+                                    user_name = (1..)
+                                        .map(|number| format!("{} {}", SLOT_NAME, number))
+                                        .find(|name| slot_list.iter().all(|slot| slot.name != *name))
+                                        .unwrap_or_default();
+                                    slot_draft = slot;
+                                    game_state = GameState::SlotName;
+                                }
+                            }
+                            Scancode::Return | Scancode::KpEnter if slot_cursor <= slot_list.len() => {
+                                let slot = &slot_list[slot_cursor - 1];
+                                let Some(slot_mode) = Mode::parse(&slot.mode) else {
+                                    eprintln!("[slots::warning] unknown mode `{}` of `{}`", slot.mode, slot.name);
+                                    continue;
+                                };
+                                let rules = if slot_mode == Mode::Daily { Rules::default() } else { cfg_rules };
+                                let slot_board =
+                                    if slot_mode == Mode::Daily { BoardMask::new(rules.board) } else { board.clone() };
+                                // played game waits in its autosave slot
+                                if slot_back == GameState::Idle && engine.score() > 0 && !analysis && !practice {
+                                    autosave(mode, &engine, &game_time, game_assists(&history, hint_count, mark_undo));
+                                }
+                                let set = if slot_mode == Mode::Daily { &daily_set } else { &figure_set };
+                                let mut slot_engine =
+                                    GameEngine::with_board(set.figures.clone(), 0, rules, &slot_board);
+                                if !restore_game(slot, &mut slot_engine, &mut game_time, &mut history, &mut hint_count)
+                                {
+                                    continue;
+                                }
+                                engine = slot_engine;
+                                mode = slot_mode;
+                                game_board = slot_board;
+                                daily_date = Date::today();
+                                // the day result is played only once, the daily game of a slot is viewed
+                                analysis = !slot.is_scored();
+                                practice = false;
+                                if slot_back == GameState::GameOver {
+                                    audio.play_music(MUSIC_BG_ID, audio::MusicLoop::Repeat);
+                                }
+                                hint = None;
+                                recorder = None;
+                                highscore = match mode {
+                                    Mode::Daily => daily_history.get(daily_date).unwrap_or(0),
+                                    _ => score_table.get_highscore(engine.rules(), mode),
+                                };
+                                (layout, field, basket, preview) = build_views(&game_board, engine.rules().hold);
+                                move_start = game_time.elapsed_millis();
+                                game_state = GameState::Idle;
+                            }
+                            Scancode::Delete if (1..=slot_list.len()).contains(&slot_cursor) => {
+                                remove_slot(&slot_list[slot_cursor - 1].path(SAVE_DIR));
+                                slot_list = read_slots();
+                                slot_cursor = slot_cursor.min(slot_list.len() + 1);
+                            }
+                            Scancode::Return | Scancode::KpEnter | Scancode::S => game_state = slot_back,
+                            _ => (),
+                        }
+                    } else if key == Scancode::S
+                        && !spectator
+                        && puzzle.is_none()
+                        && matches!(game_state, GameState::Idle | GameState::HighscoreTable | GameState::GameOver)
+                    {
+                        revert_figure(&mut current_figure, &mut recorder, &game_time, &audio);
+                        game_time.pause();
+                        slot_list = read_slots();
+                        slot_cursor = 0;
+                        slot_back = game_state;
+                        game_state = GameState::SlotSelect;
+                    }
+
+                    // choose a level or go back to the endless game
+                    if game_state == GameState::LevelSelect
+                        && let Some(pack) = &level_pack
+                    {
+                        let count = pack.levels.len();
+                        let mut leave = false;
+                        match key {
+                            Scancode::Up => level_cursor = (level_cursor + count) % (count + 1),
+                            Scancode::Down => level_cursor = (level_cursor + 1) % (count + 1),
+                            Scancode::Return | Scancode::KpEnter if level_cursor < count => {
+                                let level = &pack.levels[level_cursor];
+                                match level.start(figure_set.figures.clone()) {
+                                    Ok(level_engine) => {
+                                        let mut game = SavedGame {
+                                            engine: level_engine,
+                                            history: History::new(UNDO_LIMIT),
+                                            game_time: GameTime::new(),
+                                            hint_count: 0,
+                                            recorder: None,
+                                        };
+                                        game.swap(
+                                            &mut engine,
+                                            &mut history,
+                                            &mut game_time,
+                                            &mut hint_count,
+                                            &mut recorder,
+                                        );
+                                        // endless game waits until the puzzle mode is left
+                                        if saved_game.is_none() {
+                                            saved_game = Some(game);
+                                        }
+                                        (layout, field, basket, preview) = build_views(&level.board, false);
+                                        puzzle = Some((level_cursor, Puzzle::new(level)));
+                                        hint = None;
+                                        game_time.reset();
+                                        game_state = GameState::Idle;
+                                    }
+                                    Err(err) => eprintln!("[puzzle::warning] level `{}`: {}", level.name, err),
+                                }
+                            }
+                            // back to the unfinished level
+                            Scancode::L
+                                if puzzle
+                                    .as_ref()
+                                    .is_some_and(|(_, state)| state.status(&engine) == Status::Playing) =>
+                            {
+                                game_state = GameState::Idle
+                            }
+                            Scancode::Return | Scancode::KpEnter | Scancode::L => leave = true,
+                            _ => (),
+                        }
+                        if leave {
+                            if let Some(mut game) = saved_game.take() {
+                                game.swap(&mut engine, &mut history, &mut game_time, &mut hint_count, &mut recorder);
+                                (layout, field, basket, preview) = build_views(&game_board, engine.rules().hold);
+                            }
+                            puzzle = None;
+                            hint = None;
+                            game_state = back_state;
+                        }
+                    } else if key == Scancode::L
+                        && level_pack.is_some()
+                        && !spectator
+                        && matches!(game_state, GameState::Idle | GameState::HighscoreTable | GameState::GameOver)
+                    {
+                        if puzzle.is_none() {
+                            back_state = game_state;
+                        }
+                        revert_figure(&mut current_figure, &mut recorder, &game_time, &audio);
+                        game_time.pause();
+                        level_title = LEVEL_SELECT;
+                        game_state = GameState::LevelSelect;
+                    }
+
                     if key == Scancode::Space {
-                        process_pause(&mut game_state, &mut current_figure, &mut basket, &mut game_time, &audio);
+                        process_pause(&mut game_state, &mut current_figure, &mut recorder, &mut game_time, &audio);
+                    }
+
+                    // replay & computer player speed
+                    if spectator {
+                        match key {
+                            Scancode::Up | Scancode::KpPlus => {
+                                replay_speed = (replay_speed * 2.0).min(MAX_REPLAY_SPEED)
+                            }
+                            Scancode::Down | Scancode::KpMinus => {
+                                replay_speed = (replay_speed / 2.0).max(MIN_REPLAY_SPEED)
+                            }
+                            _ => (),
+                        }
                     }
                 }
 
@@ -440,32 +1048,76 @@ fn main() {
 
                     // GameOver -> Idle
                     if game_state == GameState::GameOver {
-                        // restart game
+                        // restart game, practice of the position ends with it
                         game_time.reset();
                         game_state = GameState::Idle;
-                        score = 0;
+                        practice = false;
+                        match replay_player.as_mut() {
+                            Some(player) => {
+                                player.rewind();
+                                engine.restart(player.seed());
+                            }
+                            None => {
+                                // daily challenge can be replayed, but only the first game is scored
+                                let seed = if mode == Mode::Daily {
+                                    daily_date.seed()
+                                } else {
+                                    cfg_seed.unwrap_or_else(time_seed)
+                                };
+                                engine.restart(seed);
+                                let set = if mode == Mode::Daily { &daily_set } else { &figure_set };
+                                recorder = Some(Replay::new(engine.seed(), set.id(), engine.rules()));
+                            }
+                        }
+                        history.clear();
+                        move_start = 0;
+                        if let Some(strategy) = autoplay.as_mut() {
+                            strategy.reset();
+                        }
+                        ai_move = None;
+                        hint = None;
+                        hint_count = 0;
                         // start playing bg music
                         audio.play_music(MUSIC_BG_ID, audio::MusicLoop::Repeat);
                         continue;
                     }
 
                     // take figure
-                    if game_state == GameState::Idle {
+                    if game_state == GameState::Idle && !spectator {
                         current_figure = match current_figure {
-                            Some(ref figure) => {
+                            Some(index) => {
                                 audio.play_sfx(SFX_CLACK_ID);
                                 let sel_pos = if magnetization { figure_pos } else { mouse_pos };
-                                if !field.set_figure(&sel_pos, figure) {
-                                    basket.ret(figure.clone());
+                                // park the figure or swap it with the held one
+                                if basket.is_hold(mouse_pos) && index != HOLD_INDEX {
+                                    record(&mut recorder, &game_time, Action::Hold { basket: index as u8 });
+                                    // parked figure is not a move, the blitz timer keeps running
+                                    hold_figure(&mut engine, &mut history, index, &mut field, &audio);
+                                    hint = None;
+                                // field is busy until clear animation is finished
+                                } else if !field.is_animated() {
+                                    let cell = field.get_cell_index(&sel_pos);
+                                    record(&mut recorder, &game_time, Action::Place { basket: index as u8, cell });
+                                    let events =
+                                        place_figure(&mut engine, &mut history, index, cell, &mut field, &audio);
+                                    if let Some((_, state)) = puzzle.as_mut() {
+                                        state.update(&events);
+                                    }
+                                    // figure dropped on the busy cell does not restart the blitz timer
+                                    if !events.is_empty() {
+                                        move_start = game_time.elapsed_millis();
+                                        hint = None;
+                                    }
                                 } else {
-                                    score += figure.blocks() * BLOCK_COST_MULTIPLIER;
+                                    record(&mut recorder, &game_time, Action::Revert);
                                 }
                                 None
                             }
                             None => {
-                                let item = basket.get(mouse_pos);
-                                if item.is_some() {
+                                let item = basket.get(mouse_pos, engine.basket());
+                                if let Some(index) = item {
                                     audio.play_sfx(SFX_CLICK_ID);
+                                    record(&mut recorder, &game_time, Action::Pick { basket: index as u8 });
                                 }
                                 item
                             }
@@ -473,46 +1125,128 @@ fn main() {
                     }
                 }
 
-                Event::ControllerAxisMotion { axis: Axis::TriggerLeft, value: AXIS_MAX, .. } => {
-                    if game_state == GameState::Idle {
-                        revert_figure(&mut current_figure, &mut basket, &audio);
-                    }
+                Event::ControllerAxisMotion { axis: Axis::TriggerLeft, value: AXIS_MAX, .. }
+                    if game_state == GameState::Idle =>
+                {
+                    revert_figure(&mut current_figure, &mut recorder, &game_time, &audio);
                 }
 
                 Event::ControllerButtonDown { button: Button::Start, .. } => {
-                    process_pause(&mut game_state, &mut current_figure, &mut basket, &mut game_time, &audio);
+                    process_pause(&mut game_state, &mut current_figure, &mut recorder, &mut game_time, &audio);
                 }
 
+                // turn the held figure
+                Event::MouseWheel { .. }
+                | Event::MouseButtonDown { mouse_btn: MouseButton::Right, .. }
+                | Event::ControllerButtonDown { button: Button::LeftShoulder | Button::RightShoulder, .. }
+                    if game_state == GameState::Idle && !spectator =>
+                {
+                    if let Some(index) = current_figure
+                        && engine.rotate(index)
+                    {
+                        audio.play_sfx(SFX_CLICK_ID);
+                        record(&mut recorder, &game_time, Action::Rotate { basket: index as u8 });
+                        hint = None;
+                    }
+                }
+
+                Event::ControllerButtonDown { button: Button::X, .. } => history_action = Some(Action::Undo),
+                Event::ControllerButtonDown { button: Button::Y, .. } => history_action = Some(Action::Redo),
+
                 _ => {}
             }
         }
 
-        // calculate score
-        if let Some(lines) = field.next_state() {
-            audio.play_sfx(SFX_CLEAR_ID);
-            score += (lines.x + lines.y + lines.x * lines.y) * LINE_MULTIPLIER;
+        // undo or redo the last move
+        if let Some(action) = history_action.filter(|_| {
+            undo_enabled && game_state == GameState::Idle && !spectator && puzzle.is_none() && mode != Mode::Daily
+        }) {
+            revert_figure(&mut current_figure, &mut recorder, &game_time, &audio);
+            // the move timer keeps running, undo does not buy time in blitz
+            if apply_history(action, &mut engine, &mut history, &mut field, &audio) {
+                record(&mut recorder, &game_time, action);
+                hint = None;
+            }
         }
 
-        // refill baskets
-        if current_figure.is_none() && game_state == GameState::Idle {
-            basket.check_and_refill(figures);
+        // clear animation
+        field.next_state();
+
+        // update highscore, levels have own scores
+        if puzzle.is_none() {
+            highscore = highscore.max(engine.score());
         }
 
-        // update highscore
-        highscore = highscore.max(score);
+        // level is finished when its goal is reached or cannot be reached anymore
+        if let Some((index, state)) = &puzzle
+            && game_state == GameState::Idle
+            && current_figure.is_none()
+            && !field.is_animated()
+        {
+            let status = state.status(&engine);
+            if status == Status::Solved && !solved.contains(index) {
+                solved.push(*index);
+                solved.sort_unstable();
+                let progress: Vec<_> = solved.iter().map(usize::to_string).collect();
+                config = config.section("puzzle").item(progress_key.as_str(), progress.join(","));
+            }
+            if status != Status::Playing {
+                game_time.pause();
+                level_cursor = if status == Status::Solved { index + 1 } else { *index };
+                level_title = if status == Status::Solved { LEVEL_SOLVED } else { LEVEL_FAILED };
+                game_state = GameState::LevelSelect;
+            }
+        }
 
-        // check gameover
-        if !field.can_set(basket.figures()) && current_figure.is_none() {
+        // check gameover, timed game is over when the time is out
+        let time_out = mode.time_left(&game_time) == Some(0);
+        if puzzle.is_none()
+            && !analysis
+            && (engine.is_game_over() && current_figure.is_none() || time_out)
+            && !field.is_animated()
+        {
             if game_state == GameState::Idle {
+                revert_figure(&mut current_figure, &mut recorder, &game_time, &audio);
                 audio.stop_music();
                 audio.play_music(MUSIC_GAMEOVER_ID, audio::MusicLoop::Once);
                 game_state = GameState::UsernameInput;
+                // write replay of the finished game
+                if let Some(replay) = recorder.take().filter(|_| save_replay) {
+                    write_replay(&replay);
+                }
+                // finished game leaves the autosave, its last position can be kept in a slot
+                if !spectator {
+                    let assists = game_assists(&history, hint_count, mark_undo);
+                    finished = Some(game_slot(String::new(), mode, &engine, &game_time, assists, true));
+                }
+                if !spectator && !practice {
+                    remove_slot(&Path::new(SAVE_DIR).join(Slot::file_name(&autosave_name(mode))));
+                }
+            }
+            // replay and computer games don't go to score table
+            // daily result goes to the daily history
+            if mode == Mode::Daily && game_state == GameState::UsernameInput {
+                if daily_history.push(daily_date, engine.score()) {
+                    config = daily_history.update_config(config);
+                }
+                field.clear(engine.clear());
+                game_state = GameState::GameOver;
+            }
+            if (spectator || practice) && game_state == GameState::UsernameInput {
+                field.clear(engine.clear());
+                game_state = GameState::GameOver;
             }
             // autoset username to score table
             if !ask_username && game_state == GameState::UsernameInput {
-                score_table.push(cfg_user_name.clone(), score, game_time.format());
-                field.clear();
-                basket.clear();
+                score_table.push(
+                    cfg_user_name.clone(),
+                    engine.score(),
+                    game_time.format(),
+                    game_assists(&history, hint_count, mark_undo),
+                    engine.rules(),
+                    mode,
+                );
+                field.clear(engine.clear());
                 game_state = GameState::GameOver;
             }
         }
@@ -520,44 +1254,349 @@ fn main() {
         // draw last frame font
         msg!(render::surface_copy(&mut canvas, &surface); canvas.window(), GT);
 
+        // render hint as figure ghost
+        if let Some(placement) = hint.filter(|_| game_state == GameState::Idle)
+            && let Some(figure) = placement.figure(engine.basket())
+        {
+            let size_1 = field.tile_size();
+            let sep = field.tile_sep();
+            let block_texture = &field.textures[&size_1.x];
+            msg!(game::render_figure(&mut canvas, &figure, &palette, block_texture, field.get_cell_point(placement.cell), size_1, sep, hint_alpha); canvas.window(), GT);
+        }
+
         // render selected figure (if they catched)
-        if let Some(figure) = &current_figure {
-            let size_1 = coord!(TILE_SIZE_1 as i16);
+        if let Some(figure) = current_figure.and_then(|index| engine.basket().get(index)) {
+            let size_1 = field.tile_size();
             let size_2 = coord!(TILE_SIZE_2 as i16);
-            let sep = coord!(TILE_SEP_1 as i16);
-            figure_pos = if field.is_point_in(&mouse_pos) && magnetization {
+            let sep = field.tile_sep();
+            figure_pos = if let Some(Action::Place { cell, .. }) = replay_player.as_ref().and_then(Player::peek) {
+                // show where replayed figure will be placed
+                field.get_cell_point(cell)
+            } else if let Some(hint::Placement { cell, .. }) = ai_move {
+                field.get_cell_point(cell)
+            } else if field.is_point_in(&mouse_pos) && magnetization {
                 field.get_point_in(&mouse_pos, figure)
             } else {
                 mouse_pos - size_2
             };
             // field already have this texture
-            let block_texture = &field.textures[&(TILE_SIZE_1 as i16)];
-            msg!(figure.render(&mut canvas, block_texture, figure_pos, size_1, sep, alpha_value); canvas.window(), GT);
+            let block_texture = &field.textures[&size_1.x];
+            msg!(game::render_figure(&mut canvas, figure, &palette, block_texture, figure_pos, size_1, sep, alpha_value); canvas.window(), GT);
         }
 
         canvas.present();
         fps_limiter.reset();
     }
 
+    // endless game is saved instead of the level
+    if let Some(mut game) = saved_game.take() {
+        game.swap(&mut engine, &mut history, &mut game_time, &mut hint_count, &mut recorder);
+    }
+    if game_state == GameState::LevelSelect || puzzle.is_some() {
+        game_state = back_state;
+    }
+    if matches!(game_state, GameState::SlotSelect | GameState::SlotName) {
+        game_state = slot_back;
+    }
+
     // save game state
-    if score > 0 && game_state == GameState::Idle || game_state == GameState::Pause {
-        let state = save::serialize(&palette, field, basket, score, &game_time);
-        config = config.section("game").item("state", state);
+    if !spectator
+        && !analysis
+        && !practice
+        && (engine.score() > 0 && game_state == GameState::Idle || game_state == GameState::Pause)
+    {
+        autosave(mode, &engine, &game_time, game_assists(&history, hint_count, mark_undo));
     }
 
     // update highscore results
     msg!(score_table.update_config(GAMESCORE_COUNT, config).to_file(CONFIG_FILE); canvas.window(), GT);
 }
 
-fn revert_figure(current_figure: &mut Option<Figure>, basket: &mut game::BasketSystem, audio: &audio::AudioSystem<'_>) {
-    if let Some(figure) = current_figure.take() {
+// layout and views of the field, baskets and preview for the board
+fn build_views(board: &BoardMask, hold: bool) -> (game::Layout, game::FieldView, game::BasketView, game::BasketView) {
+    let layout = game::Layout::new(board.size);
+    let field = game::FieldView::new(board, layout.tile_size, TILE_SEP_1, ROUND_STEPS, ROUND_RADIUS, layout.field);
+    let mut basket = game::BasketView::new(
+        BASKET_COUNT,
+        BASKET_SIZE,
+        TILE_SIZE_2,
+        TILE_SEP_2,
+        BASKET_ROUND_STEPS,
+        ROUND_RADIUS,
+        layout.basket,
+        layout.basket_shift,
+    );
+    if hold {
+        basket = basket.with_hold(layout.hold);
+    }
+    let preview = game::BasketView::new(
+        BASKET_COUNT,
+        BASKET_SIZE,
+        TILE_SIZE_3,
+        TILE_SEP_3,
+        BASKET_ROUND_STEPS,
+        PREVIEW_ROUND_RADIUS,
+        layout.preview,
+        coord!(PREVIEW_WIDTH as i16, 0),
+    );
+    (layout, field, basket, preview)
+}
+
+// framed table with the title, selected row is highlighted
+#[allow(clippy::too_many_arguments)]
+fn render_table(
+    canvas: &mut Canvas<Window>, surface: &mut Surface, font_big: &Font, font_min: &Font, title: &str, rows: &[String],
+    selected: Option<usize>, palette: &[Color],
+) -> Result<(), String> {
+    let (fsx, fsy) = font_big.size_of(title).map_err(|e| e.to_string())?;
+    let mut height = 0;
+    let mut width = fsx;
+    for row in rows {
+        let (ssx, ssy) = font_min.size_of(row).map_err(|e| e.to_string())?;
+        height += ssy as i16;
+        width = width.max(ssx);
+    }
+
+    let fp1 = coord!((W_WIDTH as i16 - width as i16) >> 1, (W_HEIGHT as i16 - fsy as i16 - height) >> 1);
+    let p1 = fp1 - 2 * BORDER;
+    let p2 = fp1 + coord!(width as i16, height + fsy as i16 - BORDER) + 2 * BORDER;
+    let p3 = p1 + BORDER;
+    let p4 = p2 - BORDER;
+
+    render::fill_rect(canvas, p1, p2, palette[12])?;
+    render::fill_rect(canvas, p3, p4, palette[8])?;
+    render::font(surface, font_big, fp1 - coord!(-10, 5), palette[10], palette[8], title)?;
+    for (index, text) in rows.iter().enumerate() {
+        let fp2 = fp1 + coord!(0, fsy as i16 + index as i16 * (height / rows.len() as i16)) - coord!(0, BORDER);
+        let fcolor = if Some(index) == selected { palette[11] } else { palette[10] };
+        render::font(surface, font_min, fp2, fcolor, palette[8], text)?;
+    }
+    Ok(())
+}
+
+// continue the game from the save slot of the mode
+fn restore_game(
+    slot: &Slot, engine: &mut GameEngine, game_time: &mut GameTime, history: &mut History, hint_count: &mut u32,
+) -> bool {
+    let mut assists = save::Assists::default();
+    // broken save is reported and the fresh game is started
+    match save::deserialize(&slot.state, engine, &mut assists) {
+        Ok(elapsed) => {
+            *game_time = GameTime::new();
+            game_time.update(elapsed);
+        }
+        Err(err) => {
+            eprintln!("[save::warning] slot `{}`: {}", slot.name, err);
+            return false;
+        }
+    }
+    history.clear();
+    history.set_used(assists.undo);
+    *hint_count = assists.hints;
+    true
+}
+
+// slot with the state of the game
+fn game_slot(
+    name: String, mode: Mode, engine: &GameEngine, game_time: &GameTime, assists: save::Assists, over: bool,
+) -> Slot {
+    Slot {
+        name,
+        mode: mode.name(),
+        score: engine.score(),
+        elapsed: game_time.elapsed_seconds(),
+        date: Date::today(),
+        over,
+        state: save::serialize(engine, game_time.elapsed_seconds(), assists),
+    }
+}
+
+// the same marks go to the score table and to the saves, undo is not marked when it is turned off in the config
+fn game_assists(history: &History, hint_count: u32, mark_undo: bool) -> save::Assists {
+    save::Assists { undo: mark_undo && history.is_used(), hints: hint_count }
+}
+
+// unfinished game of the mode is saved when the mode is left
+fn autosave_name(mode: Mode) -> String {
+    format!("{} {}", AUTOSAVE_NAME, mode.name())
+}
+
+fn autosave(mode: Mode, engine: &GameEngine, game_time: &GameTime, assists: save::Assists) {
+    write_slot(&game_slot(autosave_name(mode), mode, engine, game_time, assists, false));
+}
+
+fn read_slots() -> Vec<Slot> {
+    let mut result = Vec::new();
+    for (file, slot) in slots::list(SAVE_DIR) {
+        match slot {
+            Ok(slot) => result.push(slot),
+            Err(err) => eprintln!("[slots::warning] problem with load `{}`: {}", file.display(), err),
+        }
+    }
+    result
+}
+
+fn write_slot(slot: &Slot) {
+    if let Err(err) = slot.save(SAVE_DIR) {
+        eprintln!("[slots::warning] problem with save `{}`: {}", slot.path(SAVE_DIR).display(), err);
+    }
+}
+
+fn remove_slot(file: &Path) {
+    if file.exists()
+        && let Err(err) = fs::remove_file(file)
+    {
+        eprintln!("[slots::warning] problem with remove `{}`: {}", file.display(), err);
+    }
+}
+
+// games of the old versions are moved from the config to the autosave slots
+fn migrate_saves(mut config: Ini, engine: &GameEngine) -> Ini {
+    for mode in MODES {
+        let Some(state) = config.get::<String>("game", &mode.state_key()) else {
+            continue;
+        };
+        // broken state stays in the config
+        let mut game = engine.clone();
+        match save::deserialize(&state, &mut game, &mut save::Assists::default()) {
+            Ok(elapsed) => {
+                let slot = Slot {
+                    name: autosave_name(mode),
+                    mode: mode.name(),
+                    score: game.score(),
+                    elapsed,
+                    date: Date::today(),
+                    over: false,
+                    state,
+                };
+                write_slot(&slot);
+                config = config.section("game").erase(&mode.state_key());
+            }
+            Err(err) => eprintln!("[save::warning] {}: {}", mode.state_key(), err),
+        }
+    }
+    config
+}
+
+// time left in the timed game, move time left in the blitz and game time otherwise
+fn mode_clock(mode: Mode, game_time: &GameTime, move_start: u32, blitz_time: u32) -> String {
+    match mode {
+        Mode::Endless | Mode::Daily => game_time.format(),
+        Mode::Timed(_) => game::format_seconds(mode.time_left(game_time).unwrap_or_default()),
+        Mode::Blitz => {
+            let left = blitz_time.saturating_sub(game_time.elapsed_millis().saturating_sub(move_start));
+            format!("move {:>2}s", left.div_ceil(MILLISECOND))
+        }
+    }
+}
+
+// config key of the level pack progress
+fn progress_key(name: &str) -> String {
+    name.chars().map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_lowercase() } else { '_' }).collect()
+}
+
+fn time_seed() -> u32 {
+    let seed = match SystemTime::now().duration_since(SystemTime::UNIX_EPOCH) {
+        Ok(n) => n.as_secs(),
+        // https://xkcd.com/221/
+        Err(_) => 4,
+    };
+    seed as u32
+}
+
+fn process_events(events: Vec<engine::Event>, field: &mut game::FieldView, audio: &audio::AudioSystem<'_>) {
+    for event in events {
+        match event {
+            engine::Event::Cleared { blocks, .. } => {
+                audio.play_sfx(SFX_CLEAR_ID);
+                field.clear(blocks);
+            }
+            engine::Event::Scored { points } => field.show_points(&points),
+            _ => (),
+        }
+    }
+}
+
+fn place_figure(
+    engine: &mut GameEngine, history: &mut History, index: usize, cell: Coord, field: &mut game::FieldView,
+    audio: &audio::AudioSystem<'_>,
+) -> Vec<engine::Event> {
+    let before = engine.clone();
+    let Ok(events) = engine.place(index, cell) else {
+        return Vec::new();
+    };
+    history.push(before, events.clone());
+    process_events(events.clone(), field, audio);
+    events
+}
+
+// throw away basket figures, the penalty of the blitz timer cannot be undone
+fn skip_figures(
+    engine: &mut GameEngine, history: &mut History, field: &mut game::FieldView, audio: &audio::AudioSystem<'_>,
+) {
+    let events = engine.skip();
+    history.seal();
+    audio.play_sfx(SFX_CLACK_ID);
+    process_events(events, field, audio);
+}
+
+// park the figure in the hold slot, the swap can be undone like a placed figure
+fn hold_figure(
+    engine: &mut GameEngine, history: &mut History, index: usize, field: &mut game::FieldView,
+    audio: &audio::AudioSystem<'_>,
+) {
+    let before = engine.clone();
+    if let Ok(events) = engine.hold(index) {
+        history.push(before, events.clone());
+        process_events(events, field, audio);
+    }
+}
+
+fn apply_history(
+    action: Action, engine: &mut GameEngine, history: &mut History, field: &mut game::FieldView,
+    audio: &audio::AudioSystem<'_>,
+) -> bool {
+    let events = match action {
+        Action::Undo => history.undo(engine).then(Vec::new),
+        Action::Redo => history.redo(engine),
+        _ => None,
+    };
+    let Some(events) = events else {
+        return false;
+    };
+    // blocks of the reverted move are not animated
+    field.reset();
+    audio.play_sfx(SFX_CLACK_ID);
+    // repeated move clears the same lines
+    process_events(events, field, audio);
+    true
+}
+
+fn record(recorder: &mut Option<Replay>, game_time: &GameTime, action: Action) {
+    if let Some(replay) = recorder {
+        replay.push(game_time.elapsed_millis(), action);
+    }
+}
+
+fn write_replay(replay: &Replay) {
+    let file = Path::new(REPLAY_DIR).join(format!("{}-{}.replay", time_seed(), replay.seed));
+    if let Err(err) = fs::create_dir_all(REPLAY_DIR).and_then(|_| replay.save(&file)) {
+        eprintln!("[replay::warning] problem with save `{}`: {}", file.display(), err);
+    }
+}
+
+fn revert_figure(
+    current_figure: &mut Option<usize>, recorder: &mut Option<Replay>, game_time: &GameTime,
+    audio: &audio::AudioSystem<'_>,
+) {
+    if current_figure.take().is_some() {
         audio.play_sfx(SFX_CLACK_ID);
-        basket.ret(figure);
+        record(recorder, game_time, Action::Revert);
     }
 }
 
 fn process_pause(
-    game_state: &mut GameState, current_figure: &mut Option<Figure>, basket: &mut game::BasketSystem,
+    game_state: &mut GameState, current_figure: &mut Option<usize>, recorder: &mut Option<Replay>,
     game_time: &mut GameTime, audio: &audio::AudioSystem<'_>,
 ) {
     match game_state {
@@ -565,7 +1604,7 @@ fn process_pause(
             *game_state = GameState::Pause;
             game_time.pause();
 
-            revert_figure(current_figure, basket, audio);
+            revert_figure(current_figure, recorder, game_time, audio);
         }
         GameState::Pause => *game_state = GameState::Idle,
         _ => (),
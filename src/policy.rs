@@ -0,0 +1,57 @@
+use std::fmt;
+use std::str::FromStr;
+
+// how figures are chosen for the basket
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Policy {
+    // any figure with the same chance, it differs from `rand() % size` of the first versions
+    // only for the rejected values at the top of the range
+    #[default]
+    Uniform,
+    // figure probability is proportional to its weight
+    Weighted,
+    // shuffled bag with every figure `weight` times
+    Bag,
+    // uniform, but figures are not repeated in one refill
+    NoRepeat,
+}
+
+pub const POLICIES: [Policy; 4] = [Policy::Uniform, Policy::Weighted, Policy::Bag, Policy::NoRepeat];
+
+impl Policy {
+    pub fn id(self) -> u8 {
+        match self {
+            Policy::Uniform => 0,
+            Policy::Weighted => 1,
+            Policy::Bag => 2,
+            Policy::NoRepeat => 3,
+        }
+    }
+
+    pub fn from_id(id: u8) -> Option<Policy> {
+        POLICIES.into_iter().find(|policy| policy.id() == id)
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Policy::Uniform => "uniform",
+            Policy::Weighted => "weighted",
+            Policy::Bag => "bag",
+            Policy::NoRepeat => "no_repeat",
+        }
+    }
+}
+
+impl FromStr for Policy {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Policy, String> {
+        POLICIES.into_iter().find(|policy| policy.name() == value).ok_or_else(|| format!("unknown policy `{}`", value))
+    }
+}
+
+impl fmt::Display for Policy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
@@ -0,0 +1,322 @@
+use std::fs;
+use std::path::Path;
+
+use crate::board::{self, BoardMask, Cell};
+use crate::engine::{Block, Event, FIELD_LEN, FIGURE_COLORS, Field, Figure, GameEngine, Rules};
+use crate::figures::{self, MAX_FIGURE_ID};
+
+const COMMENT: char = ';';
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Goal {
+    // no blocks are left on the field
+    Clear,
+    // remove this number of lines
+    Lines(u32),
+    // get this score
+    Score(u32),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    Playing,
+    Solved,
+    Failed,
+}
+
+// hand-made level with the fixed figure order
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Level {
+    pub name: String,
+    pub board: BoardMask,
+    // blocks on the field at the start
+    pub blocks: Vec<Block>,
+    // figure ids in the order they are dealt
+    pub figures: Vec<u8>,
+    pub goal: Goal,
+    // moves limit
+    pub moves: Option<u32>,
+}
+
+// set of levels
+//
+// file format:
+//   ; comment
+//   name <pack name>
+//
+//   level <level name>
+//   goal clear | lines <count> | score <value>
+//   [moves <count>]
+//   figures <id> <id> ...
+//   ..33......
+//   ..##......
+//
+// rows are the initial field, `0`..`7` is a block with the color and other symbols are the same as
+// in board masks, level without rows is played on the empty classic field
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LevelPack {
+    pub name: String,
+    pub levels: Vec<Level>,
+}
+
+// level header and field rows
+struct Draft {
+    line: usize,
+    name: String,
+    goal: Option<Goal>,
+    moves: Option<u32>,
+    figures: Vec<u8>,
+    rows: Vec<String>,
+}
+
+// progress of the played level
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Puzzle {
+    pub goal: Goal,
+    pub moves: Option<u32>,
+    made: u32,
+    lines: u32,
+}
+
+impl Draft {
+    fn build(self) -> Result<Level, String> {
+        let line = self.line;
+        let goal = self.goal.ok_or_else(|| format!("line {}: level `{}` has no goal", line, self.name))?;
+        if self.figures.is_empty() {
+            return Err(format!("line {}: level `{}` has no figures", line, self.name));
+        }
+        if let Some(&id) = self.figures.iter().find(|&&id| id == 0 || id > MAX_FIGURE_ID) {
+            return Err(format!("line {}: figure id {} must be in 1..={}", line, id, MAX_FIGURE_ID));
+        }
+
+        let mut rows = Vec::new();
+        let mut blocks = Vec::new();
+        for (y, text) in self.rows.iter().enumerate() {
+            let mut row = Vec::new();
+            for (x, c) in text.chars().enumerate() {
+                if let Some(color) = c.to_digit(10).filter(|&color| color < FIGURE_COLORS as u32) {
+                    blocks.push(Block { pos: coord!(x as i16, y as i16), color: color as u8 });
+                    row.push(Cell::Free);
+                    continue;
+                }
+                row.push(board::cell(c).ok_or_else(|| format!("line {}: unknown cell symbol `{}`", line + y + 1, c))?);
+            }
+            rows.push(row);
+        }
+        let board = if rows.is_empty() {
+            BoardMask::new(coord!(FIELD_LEN as i16))
+        } else {
+            BoardMask::from_rows(self.name.clone(), rows).map_err(|err| format!("line {}: {}", line, err))?
+        };
+
+        let level = Level { name: self.name, board, blocks, figures: self.figures, goal, moves: self.moves };
+        if level.field().next_state().is_some() {
+            return Err(format!("line {}: level `{}` starts with full lines", line, level.name));
+        }
+        Ok(level)
+    }
+}
+
+impl Level {
+    // game of the level with figures from the set
+    pub fn start(&self, figures: Vec<Figure>) -> Result<GameEngine, String> {
+        if let Some(id) = self.figures.iter().find(|&&id| !figures.iter().any(|figure| figure.index == id)) {
+            return Err(format!("figure id {} is not in the figure set", id));
+        }
+        // classic rules on the level board
+        let mut engine = GameEngine::with_board(figures, 0, Rules::default(), &self.board);
+        *engine.field_mut() = self.field();
+        engine.set_sequence(self.figures.clone());
+        Ok(engine)
+    }
+
+    // field of the level board with the initial blocks
+    pub fn field(&self) -> Field {
+        let mut field = self.board.field();
+        for Block { pos, color } in &self.blocks {
+            field.set(*pos, *color);
+        }
+        field
+    }
+}
+
+impl LevelPack {
+    pub fn parse(data: &str) -> Result<LevelPack, String> {
+        let mut name = None;
+        let mut drafts: Vec<Draft> = Vec::new();
+
+        for (number, text) in data.lines().enumerate() {
+            let line = number + 1;
+            let text = text.split(COMMENT).next().unwrap_or_default().trim();
+            let mut words = text.split_whitespace();
+            let keyword = words.next().unwrap_or_default();
+            let rest = text[keyword.len()..].trim();
+            if keyword.is_empty() {
+                continue;
+            }
+            if keyword == "name" {
+                name = Some(rest.to_string());
+                continue;
+            }
+            if keyword == "level" {
+                let name = if rest.is_empty() { format!("level {}", drafts.len() + 1) } else { rest.to_string() };
+                drafts.push(Draft { line, name, goal: None, moves: None, figures: Vec::new(), rows: Vec::new() });
+                continue;
+            }
+            let Some(draft) = drafts.last_mut() else {
+                return Err(format!("line {}: `{}` without level header", line, keyword));
+            };
+            match keyword {
+                "goal" => {
+                    draft.goal = Some(match words.next() {
+                        Some("clear") => Goal::Clear,
+                        Some("lines") => Goal::Lines(figures::number(line, "lines", words.next())?),
+                        Some("score") => Goal::Score(figures::number(line, "score", words.next())?),
+                        _ => return Err(format!("line {}: goal must be `clear`, `lines <n>` or `score <n>`", line)),
+                    })
+                }
+                "moves" => draft.moves = Some(figures::number(line, keyword, words.next())?),
+                "figures" => {
+                    for id in words {
+                        draft.figures.push(figures::number(line, "figure id", Some(id))?);
+                    }
+                }
+                _ if keyword.chars().all(|c| c.is_ascii_digit() || board::cell(c).is_some()) => {
+                    draft.rows.push(text.to_string())
+                }
+                _ => return Err(format!("line {}: unknown keyword `{}`", line, keyword)),
+            }
+        }
+
+        let levels = drafts.into_iter().map(Draft::build).collect::<Result<Vec<_>, _>>()?;
+        if levels.is_empty() {
+            return Err("level pack has no levels".to_string());
+        }
+        let name = name.filter(|name| !name.is_empty()).unwrap_or_else(|| "custom".to_string());
+        Ok(LevelPack { name, levels })
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<LevelPack, String> {
+        let data = fs::read_to_string(path).map_err(|e| e.to_string())?;
+        LevelPack::parse(&data)
+    }
+}
+
+impl Puzzle {
+    pub fn new(level: &Level) -> Puzzle {
+        Puzzle { goal: level.goal, moves: level.moves, made: 0, lines: 0 }
+    }
+
+    // count moves and removed lines
+    pub fn update(&mut self, events: &[Event]) {
+        for event in events {
+            match event {
+                Event::Placed { .. } => self.made += 1,
+                Event::Cleared { lines, .. } => self.lines += lines.x + lines.y,
+                _ => (),
+            }
+        }
+    }
+
+    pub fn status(&self, engine: &GameEngine) -> Status {
+        let solved = match self.goal {
+            Goal::Clear => self.made > 0 && engine.field().is_empty(),
+            Goal::Lines(count) => self.lines >= count,
+            Goal::Score(value) => engine.score() >= value,
+        };
+        if solved {
+            return Status::Solved;
+        }
+        let no_moves = self.moves.is_some_and(|moves| self.made >= moves);
+        if no_moves || engine.is_out_of_figures() || engine.is_game_over() {
+            return Status::Failed;
+        }
+        Status::Playing
+    }
+
+    // goal and moves left as short text
+    pub fn progress(&self, engine: &GameEngine) -> (String, Option<String>) {
+        let goal = match self.goal {
+            Goal::Clear => format!("clear {}", engine.field().blocks().len()),
+            Goal::Lines(count) => format!("lines {}/{}", self.lines.min(count), count),
+            Goal::Score(value) => format!("{}/{}", engine.score().min(value), value),
+        };
+        (goal, self.moves.map(|moves| format!("moves {}", moves.saturating_sub(self.made))))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::default_figures;
+    use crate::figure;
+
+    const PACK: &str = "name test\n\nlevel one line ; comment\ngoal lines 1\nmoves 1\nfigures 5 3\n\
+                        ..........\n..........\n..........\n..........\n..........\n\
+                        ..........\n..........\n..........\n..........\n.....11111\n\n\
+                        level\ngoal score 20\nfigures 3 3\n";
+
+    #[test]
+    fn resource_file() {
+        let pack = LevelPack::parse(include_str!("../resources/levels.txt")).unwrap();
+        for level in &pack.levels {
+            assert!(level.start(default_figures()).is_ok(), "{}", level.name);
+        }
+    }
+
+    #[test]
+    fn parse_pack() {
+        let pack = LevelPack::parse(PACK).unwrap();
+        assert_eq!(pack.name, "test");
+        assert_eq!(pack.levels.len(), 2);
+
+        let level = &pack.levels[0];
+        assert_eq!(level.name, "one line");
+        assert_eq!(level.goal, Goal::Lines(1));
+        assert_eq!(level.moves, Some(1));
+        assert_eq!(level.figures, vec![5, 3]);
+        assert_eq!(level.blocks.len(), 5);
+        assert_eq!(level.blocks[0], Block { pos: coord!(5, 9), color: 1 });
+        assert_eq!(pack.levels[1].name, "level 2");
+        assert_eq!(pack.levels[1].board.size, coord!(10, 10));
+    }
+
+    #[test]
+    fn solve_and_fail() {
+        let pack = LevelPack::parse(PACK).unwrap();
+        let level = &pack.levels[0];
+
+        let mut engine = level.start(default_figures()).unwrap();
+        let mut puzzle = Puzzle::new(level);
+        assert_eq!(puzzle.status(&engine), Status::Playing);
+        puzzle.update(&engine.place(0, coord!(0, 9)).unwrap());
+        assert_eq!(puzzle.status(&engine), Status::Solved);
+        assert_eq!(puzzle.progress(&engine), ("lines 1/1".to_string(), Some("moves 0".to_string())));
+
+        // the only move is spent for nothing
+        let mut engine = level.start(default_figures()).unwrap();
+        let mut puzzle = Puzzle::new(level);
+        puzzle.update(&engine.place(0, coord!(0, 0)).unwrap());
+        assert_eq!(puzzle.status(&engine), Status::Failed);
+
+        assert!(level.start(vec![figure!(5, 0; (0, 0))]).is_err());
+    }
+
+    #[test]
+    fn validation() {
+        let errors = [
+            "goal clear\nfigures 1",
+            "level\nfigures 1",
+            "level\ngoal clear",
+            "level\ngoal win\nfigures 1",
+            "level\ngoal clear\nfigures 0",
+            "level\ngoal clear\nfigures 1\n.....\n.....\n..x..\n.....\n.....",
+            "level\ngoal clear\nfigures 1\n.....\n.....\n22222\n.....\n.....",
+            "level\ngoal clear\nfigures 1\nsize 5",
+            "name empty",
+        ];
+        for data in errors {
+            assert!(LevelPack::parse(data).is_err(), "{:?}", data);
+        }
+    }
+}
@@ -1,9 +1,11 @@
 // https://en.wikipedia.org/wiki/Xorshift
+#[derive(Debug, Clone)]
 pub struct Random(u32);
 
 impl Random {
     pub fn new(seed: u32) -> Random {
-        Random(seed)
+        // xorshift never leaves zero state
+        Random(if seed == 0 { u32::MAX } else { seed })
     }
 
     pub fn rand(&mut self) -> u32 {
@@ -12,4 +14,19 @@ impl Random {
         self.0 ^= self.0 << 5;
         self.0
     }
+
+    // value in 0..n without modulo bias
+    pub fn below(&mut self, n: u32) -> u32 {
+        let zone = u32::MAX - u32::MAX % n;
+        loop {
+            let value = self.rand();
+            if value < zone {
+                return value % n;
+            }
+        }
+    }
+
+    pub fn state(&self) -> u32 {
+        self.0
+    }
 }
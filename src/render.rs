@@ -1,4 +1,6 @@
-use crate::extra::{BlendColor, Coord, RectData};
+use rs_1010::coord::Coord;
+
+use crate::extra::{BlendColor, RectData};
 
 use sdl2::pixels::Color;
 use sdl2::rect::{Point, Rect};
@@ -33,7 +35,7 @@ pub fn build_rounded_rect(c1: Coord, c2: Coord, steps: i16, r: i16) -> RectData
         .collect();
 
     // reorder
-    v.sort_by(|a, b| a.1.cmp(&b.1));
+    v.sort_by_key(|a| a.1);
     // min and max of x and y
     let (min_y, max_y) = (v[0].1, v[v.len() - 1].1);
     let (mut min_x, mut max_x) = (0, 0);
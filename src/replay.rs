@@ -0,0 +1,384 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::board::BoardMask;
+use crate::codec::{Decoder, Encoder};
+use crate::coord::Coord;
+use crate::engine::{self, Rules};
+use crate::policy::Policy;
+
+// replay bits
+const REPLAY_VERSION: u8 = 2;
+const REPLAY_VERSION_SIZE: u8 = 8;
+const REPLAY_SEED_SIZE: u8 = 32;
+const REPLAY_FIGURE_SET_SIZE: u8 = 32;
+const REPLAY_POLICY_SIZE: u8 = 4;
+const REPLAY_RULES_SIZE: u8 = 8;
+const REPLAY_BOARD_SIZE: u8 = 5;
+const REPLAY_MASK_SIZE: u8 = 32;
+const REPLAY_COUNT_SIZE: u8 = 32;
+const REPLAY_TICK_SIZE: u8 = 24;
+const REPLAY_ACTION_SIZE: u8 = 3;
+const REPLAY_BASKET_SIZE: u8 = 3;
+// drop cell is clamped to 0..=16, the first version dropped 16 into 0
+const REPLAY_POS_SIZE: u8 = 5;
+const REPLAY_POS_SIZE_V1: u8 = 4;
+
+const REPLAY_MAX_DELTA: u32 = (1 << REPLAY_TICK_SIZE) - 1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    // figure is taken from the basket
+    Pick { basket: u8 },
+    // figure is released over the field cell
+    Place { basket: u8, cell: Coord },
+    // figure is returned to the basket
+    Revert,
+    // last move is reverted
+    Undo,
+    // reverted move is repeated
+    Redo,
+    // figure in the basket is turned clockwise
+    Rotate { basket: u8 },
+    // figure in the basket is swapped with the hold slot
+    Hold { basket: u8 },
+    // basket figures are thrown away by the move timer
+    Skip,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Move {
+    // game time in milliseconds
+    pub tick: u32,
+    pub action: Action,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Replay {
+    pub seed: u32,
+    // id of the figure set
+    pub figures: u32,
+    pub rules: Rules,
+    pub moves: Vec<Move>,
+}
+
+pub struct Player {
+    replay: Replay,
+    position: usize,
+    clock: f64,
+}
+
+impl Action {
+    fn id(&self) -> u8 {
+        match self {
+            Action::Pick { .. } => 0,
+            Action::Place { .. } => 1,
+            Action::Revert => 2,
+            Action::Undo => 3,
+            Action::Redo => 4,
+            Action::Rotate { .. } => 5,
+            Action::Hold { .. } => 6,
+            Action::Skip => 7,
+        }
+    }
+}
+
+impl Replay {
+    pub fn new(seed: u32, figures: u32, rules: Rules) -> Replay {
+        Replay { seed, figures, rules, moves: Vec::new() }
+    }
+
+    pub fn push(&mut self, tick: u32, action: Action) {
+        self.moves.push(Move { tick, action });
+    }
+
+    pub fn encode(&self) -> String {
+        let mut encoder = Encoder::new();
+
+        encoder.push(REPLAY_VERSION, REPLAY_VERSION_SIZE);
+        encoder.push(self.seed, REPLAY_SEED_SIZE);
+        encoder.push(self.figures, REPLAY_FIGURE_SET_SIZE);
+        encoder.push(self.rules.policy.id(), REPLAY_POLICY_SIZE);
+        encoder.push(self.rules.flags(), REPLAY_RULES_SIZE);
+        encoder.push(self.rules.board.x, REPLAY_BOARD_SIZE);
+        encoder.push(self.rules.board.y, REPLAY_BOARD_SIZE);
+        encoder.push(self.rules.mask, REPLAY_MASK_SIZE);
+        encoder.push(self.moves.len() as u32, REPLAY_COUNT_SIZE);
+
+        let mut last_tick = 0;
+        for Move { tick, action } in &self.moves {
+            // store only time between moves
+            let delta = tick.saturating_sub(last_tick).min(REPLAY_MAX_DELTA);
+            last_tick += delta;
+            encoder.push(delta, REPLAY_TICK_SIZE);
+            encoder.push(action.id(), REPLAY_ACTION_SIZE);
+            match action {
+                Action::Pick { basket } | Action::Rotate { basket } | Action::Hold { basket } => {
+                    encoder.push(*basket, REPLAY_BASKET_SIZE);
+                }
+                Action::Place { basket, cell } => {
+                    encoder.push(*basket, REPLAY_BASKET_SIZE);
+                    encoder.push(cell.x, REPLAY_POS_SIZE);
+                    encoder.push(cell.y, REPLAY_POS_SIZE);
+                }
+                Action::Revert | Action::Undo | Action::Redo | Action::Skip => (),
+            }
+        }
+
+        encoder.result()
+    }
+
+    pub fn decode(data: &str) -> Option<Replay> {
+        let mut decoder = Decoder::decode(data.trim()).ok()?;
+
+        let pos_size = match decoder.take::<u8>(REPLAY_VERSION_SIZE).ok()? {
+            1 => REPLAY_POS_SIZE_V1,
+            REPLAY_VERSION => REPLAY_POS_SIZE,
+            _ => return None,
+        };
+        let seed = decoder.take(REPLAY_SEED_SIZE).ok()?;
+        let figures = decoder.take(REPLAY_FIGURE_SET_SIZE).ok()?;
+        let policy = Policy::from_id(decoder.take(REPLAY_POLICY_SIZE).ok()?)?;
+        let flags = decoder.take(REPLAY_RULES_SIZE).ok()?;
+        let board = coord!(decoder.take(REPLAY_BOARD_SIZE).ok()?, decoder.take(REPLAY_BOARD_SIZE).ok()?);
+        if !engine::is_board_size(board) {
+            return None;
+        }
+        let mask = decoder.take(REPLAY_MASK_SIZE).ok()?;
+        let mut replay =
+            Replay::new(seed, figures, Rules { policy, board, mask, ..Rules::default() }.with_flags(flags)?);
+
+        let mut tick = 0;
+        for _ in 0..decoder.take::<u32>(REPLAY_COUNT_SIZE).ok()? {
+            tick += decoder.take::<u32>(REPLAY_TICK_SIZE).ok()?;
+            let action = match decoder.take::<u8>(REPLAY_ACTION_SIZE).ok()? {
+                0 => Action::Pick { basket: decoder.take(REPLAY_BASKET_SIZE).ok()? },
+                1 => {
+                    let basket = decoder.take(REPLAY_BASKET_SIZE).ok()?;
+                    let x = decoder.take(pos_size).ok()?;
+                    let y = decoder.take(pos_size).ok()?;
+                    Action::Place { basket, cell: coord!(x, y) }
+                }
+                2 => Action::Revert,
+                3 => Action::Undo,
+                4 => Action::Redo,
+                5 => Action::Rotate { basket: decoder.take(REPLAY_BASKET_SIZE).ok()? },
+                6 => Action::Hold { basket: decoder.take(REPLAY_BASKET_SIZE).ok()? },
+                7 => Action::Skip,
+                _ => return None,
+            };
+            replay.push(tick, action);
+        }
+
+        Some(replay)
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        fs::write(path, self.encode())
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Replay> {
+        let data = fs::read_to_string(path)?;
+        Replay::decode(&data).ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "broken replay file"))
+    }
+}
+
+impl Player {
+    pub fn new(replay: Replay) -> Player {
+        Player { replay, position: 0, clock: 0.0 }
+    }
+
+    pub fn seed(&self) -> u32 {
+        self.replay.seed
+    }
+
+    pub fn figures(&self) -> u32 {
+        self.replay.figures
+    }
+
+    pub fn rules(&self) -> Rules {
+        self.replay.rules
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.position >= self.replay.moves.len()
+    }
+
+    pub fn rewind(&mut self) {
+        self.position = 0;
+        self.clock = 0.0;
+    }
+
+    // board of the recorded game, the mask is used only if the game was played on it
+    pub fn board(&self, mask: &BoardMask) -> BoardMask {
+        let rules = self.replay.rules;
+        if rules.mask != 0 && rules.mask == mask.id() && rules.board == mask.size {
+            mask.clone()
+        } else {
+            BoardMask::new(rules.board)
+        }
+    }
+
+    // next action which is not played yet
+    pub fn peek(&self) -> Option<Action> {
+        self.replay.moves.get(self.position).map(|m| m.action)
+    }
+
+    // move replay clock by `delta` milliseconds and return actions to play
+    pub fn advance(&mut self, delta: u32, speed: f64) -> Vec<Action> {
+        self.clock += delta as f64 * speed;
+        let mut actions = Vec::new();
+        while let Some(Move { tick, action }) = self.replay.moves.get(self.position) {
+            if *tick as f64 > self.clock {
+                break;
+            }
+            actions.push(*action);
+            self.position += 1;
+        }
+        actions
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::{BASKET_COUNT, GameEngine, default_figures};
+    use crate::figures::FigureSet;
+    use crate::scoring::Scoring;
+
+    fn sample() -> Replay {
+        let mut replay = Replay::new(
+            1010,
+            0xdead,
+            Rules {
+                policy: Policy::Bag,
+                fair: true,
+                rotation: true,
+                hold: true,
+                board: coord!(12, 8),
+                mask: 0xbeef,
+                scoring: Scoring::Combo,
+            },
+        );
+        replay.push(500, Action::Pick { basket: 2 });
+        replay.push(1200, Action::Place { basket: 2, cell: coord!(7, 3) });
+        replay.push(1900, Action::Pick { basket: 0 });
+        replay.push(2000, Action::Revert);
+        replay.push(3500, Action::Place { basket: 1, cell: coord!(0, 10) });
+        replay.push(3600, Action::Undo);
+        replay.push(3700, Action::Redo);
+        replay.push(3800, Action::Rotate { basket: 1 });
+        replay.push(3900, Action::Hold { basket: 0 });
+        replay.push(4000, Action::Skip);
+        replay
+    }
+
+    #[test]
+    fn round_trip() {
+        let replay = sample();
+        assert_eq!(Replay::decode(&replay.encode()), Some(replay));
+    }
+
+    #[test]
+    fn empty_replay() {
+        let replay = Replay::new(42, FigureSet::default().id(), Rules { policy: Policy::NoRepeat, ..Rules::default() });
+        assert_eq!(Replay::decode(&replay.encode()), Some(replay));
+    }
+
+    #[test]
+    fn unknown_version() {
+        let mut encoder = Encoder::new();
+        encoder.push(REPLAY_VERSION + 1, REPLAY_VERSION_SIZE);
+        encoder.push(7_u32, REPLAY_SEED_SIZE);
+        assert_eq!(Replay::decode(&encoder.result()), None);
+    }
+
+    #[test]
+    fn player_speed() {
+        let mut player = Player::new(sample());
+        assert_eq!(player.advance(250, 1.0), vec![]);
+        assert_eq!(player.advance(250, 1.0), vec![Action::Pick { basket: 2 }]);
+        assert_eq!(player.peek(), Some(Action::Place { basket: 2, cell: coord!(7, 3) }));
+        // 500 + 2 * 750 = 2000
+        assert_eq!(player.advance(750, 2.0).len(), 3);
+        assert!(!player.is_finished());
+        assert_eq!(player.advance(2000, 1.0).len(), 6);
+        assert!(player.is_finished());
+
+        player.rewind();
+        assert_eq!(player.peek(), Some(Action::Pick { basket: 2 }));
+    }
+
+    #[test]
+    fn rectangle_board() {
+        let board = BoardMask::new(coord!(12, 12));
+        let rules = Rules { board: board.size, ..Rules::default() };
+        let mut engine = GameEngine::with_board(default_figures(), 3, rules, &board);
+        // column out of the classic board
+        let (index, cell) = (0..BASKET_COUNT as usize)
+            .flat_map(|index| (0..board.size.y).map(move |y| (index, coord!(10, y))))
+            .find(|&(index, cell)| engine.clone().place(index, cell).is_ok())
+            .unwrap();
+        let mut replay = Replay::new(engine.seed(), FigureSet::default().id(), engine.rules());
+        replay.push(100, Action::Place { basket: index as u8, cell });
+        engine.place(index, cell).unwrap();
+
+        // classic board of the config has the same mask id as any rectangle
+        let player = Player::new(Replay::decode(&replay.encode()).unwrap());
+        let replayed = player.board(&BoardMask::new(Rules::default().board));
+        assert_eq!(replayed.size, board.size);
+        let mut copy = GameEngine::with_board(default_figures(), player.seed(), player.rules(), &replayed);
+        assert_eq!(player.peek(), Some(Action::Place { basket: index as u8, cell }));
+        copy.place(index, cell).unwrap();
+        assert_eq!(copy.score(), engine.score());
+        assert_eq!(copy.field().blocks(), engine.field().blocks());
+    }
+
+    #[test]
+    fn off_field_drop() {
+        let board = BoardMask::new(coord!(16, 16));
+        let rules = Rules { board: board.size, ..Rules::default() };
+        let mut engine = GameEngine::with_board(default_figures(), 5, rules, &board);
+        // cursor right of the field is clamped to the column after the last one
+        let dropped = coord!(16, 3);
+        assert!(engine.place(0, dropped).is_err());
+        engine.place(0, coord!(5, 7)).unwrap();
+        let mut replay = Replay::new(engine.seed(), FigureSet::default().id(), engine.rules());
+        replay.push(100, Action::Place { basket: 0, cell: dropped });
+        replay.push(200, Action::Place { basket: 0, cell: coord!(5, 7) });
+
+        let mut player = Player::new(Replay::decode(&replay.encode()).unwrap());
+        let mut copy = GameEngine::with_board(default_figures(), player.seed(), player.rules(), &player.board(&board));
+        let actions = player.advance(200, 1.0);
+        assert_eq!(actions[0], Action::Place { basket: 0, cell: dropped });
+        for action in actions {
+            if let Action::Place { basket, cell } = action {
+                let _ = copy.place(basket as usize, cell);
+            }
+        }
+        assert_eq!(copy.field().blocks(), engine.field().blocks());
+    }
+
+    #[test]
+    fn first_version() {
+        let mut encoder = Encoder::new();
+        encoder.push(1, REPLAY_VERSION_SIZE);
+        encoder.push(7_u32, REPLAY_SEED_SIZE);
+        encoder.push(FigureSet::default().id(), REPLAY_FIGURE_SET_SIZE);
+        encoder.push(Policy::Uniform.id(), REPLAY_POLICY_SIZE);
+        encoder.push(Rules::default().flags(), REPLAY_RULES_SIZE);
+        encoder.push(10, REPLAY_BOARD_SIZE);
+        encoder.push(10, REPLAY_BOARD_SIZE);
+        encoder.push(0, REPLAY_MASK_SIZE);
+        encoder.push(1_u32, REPLAY_COUNT_SIZE);
+        encoder.push(100, REPLAY_TICK_SIZE);
+        encoder.push(1, REPLAY_ACTION_SIZE);
+        encoder.push(2, REPLAY_BASKET_SIZE);
+        encoder.push(7, REPLAY_POS_SIZE_V1);
+        encoder.push(3, REPLAY_POS_SIZE_V1);
+
+        let replay = Replay::decode(&encoder.result()).unwrap();
+        assert_eq!(replay.moves, vec![Move { tick: 100, action: Action::Place { basket: 2, cell: coord!(7, 3) } }]);
+    }
+}
@@ -1,101 +1,509 @@
-use sdl2::pixels::Color;
+use std::fmt;
 
-use crate::codec::{Decoder, Encoder};
-use crate::consts::*;
-use crate::game::GameTime;
-use crate::game::{BasketSystem, Field, Figure};
+use crate::codec::{self, Decoder, Encoder};
+use crate::coord::Coord;
+use crate::engine::{BASKET_COUNT, Block, FIELD_LEN, FIGURE_COLORS, GameEngine, HOLD_INDEX, Rules};
+use crate::figures::{self, FigureSet};
+use crate::policy::Policy;
+use crate::scoring::Scoring;
 
-pub fn serialize(palette: &[Color], field: Field, bsystem: BasketSystem, score: u32, game_time: &GameTime) -> String {
-    let mut encoder = Encoder::new();
+// serde bits
+const SERDE_VERSION: u8 = 8;
+// versions 1 and 2 are the saves without the header
+const SERDE_VERSION_CURRENT: u8 = 3;
+// header and state are split by the symbol out of the save alphabet
+const SERDE_SEPARATOR: char = '.';
+const SERDE_CHECKSUM: u8 = 32;
+const SERDE_STREAK: u8 = 16;
+const SERDE_BLOCKS: u8 = 16;
+const SERDE_FIELD_SIZE: u8 = 1;
+const SERDE_FIGURE_SIZE: u8 = 5;
+const SERDE_SCORE_SIZE: u8 = 32;
+const SERDE_TIME_SIZE: u8 = 64;
+const SERDE_PADDING_SIZE: u8 = 5;
+const SERDE_V2_SUPPORT: u8 = 0b10101;
+const SERDE_COLOR: u8 = 8;
+const SERDE_POS: u8 = 4;
+const SERDE_SEED: u8 = 32;
+const SERDE_FLAGS: u8 = 8;
+const SERDE_HINTS: u8 = 16;
+const SERDE_FIGURE_SET: u8 = 32;
+const SERDE_POLICY: u8 = 4;
+const SERDE_BAG_SIZE: u8 = 16;
+const SERDE_RULES: u8 = 8;
+const SERDE_TURNS: u8 = 2;
+const SERDE_BOARD: u8 = 5;
+const SERDE_MASK: u8 = 32;
+// position codes have own versions, so saves and codes are not mixed up
+const SERDE_SHARE_VERSION: u8 = 128;
 
-    let mut color_data = Vec::new();
-
-    // Game field state
-    for y in 0..field.field_size.y {
-        for x in 0..field.field_size.x {
-            let pos = coord!(x, y);
-            let is_set = field.is_set(&pos);
-            encoder.push(is_set, SERDE_FIELD_SIZE);
-            if is_set {
-                if let Some(color) = field.get_color(&pos) {
-                    let color_index = palette.iter().position(|i| i == color).unwrap_or(0) as u8;
-                    color_data.push((pos, color_index));
-                }
-            }
+// used game assists, they are not part of the engine state
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Assists {
+    pub undo: bool,
+    pub hints: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SaveError {
+    // data ends before the state is read
+    Truncated,
+    // symbol out of the save alphabet
+    BadSymbol,
+    // header does not match the data
+    BadChecksum,
+    UnknownVersion(u8),
+    // figure id is not in the figure set
+    BadFigure(u8),
+    BadColor(u8),
+    // block is out of the board or on the blocked cell
+    BadCell(Coord),
+    BadPolicy(u8),
+    BadRules(u8),
+    // game is saved with other figures or board
+    OtherFigureSet,
+    OtherBoard,
+}
+
+// header of the versioned save
+struct Header {
+    version: u8,
+    board: Coord,
+    figures: u32,
+    checksum: u32,
+}
+
+impl Assists {
+    fn flags(self) -> u8 {
+        self.undo as u8
+    }
+
+    fn set_flags(&mut self, bits: u8) {
+        self.undo = bits & 1 == 1;
+    }
+}
+
+impl fmt::Display for SaveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SaveError::Truncated => write!(f, "saved game is truncated"),
+            SaveError::BadSymbol => write!(f, "saved game has unknown symbols"),
+            SaveError::BadChecksum => write!(f, "saved game is corrupted"),
+            SaveError::UnknownVersion(version) => write!(f, "unknown save version {}", version),
+            SaveError::BadFigure(id) => write!(f, "unknown figure id {} in saved game", id),
+            SaveError::BadColor(color) => write!(f, "unknown block color {} in saved game", color),
+            SaveError::BadCell(pos) => write!(f, "block ({}, {}) is out of the board", pos.x, pos.y),
+            SaveError::BadPolicy(id) => write!(f, "unknown figure policy {} in saved game", id),
+            SaveError::BadRules(flags) => write!(f, "unknown rules {:#010b} in saved game", flags),
+            SaveError::OtherFigureSet => write!(f, "saved game uses other figure set"),
+            SaveError::OtherBoard => write!(f, "saved game uses other board"),
         }
     }
+}
 
-    // Figures in basket
-    for basket in bsystem.destroy() {
-        let value = match basket.figure() {
-            Some(figure) => figure.index,
-            None => 0,
-        };
-        encoder.push(value, SERDE_FIGURE_SIZE);
+impl Header {
+    fn encode(&self) -> String {
+        let mut encoder = Encoder::new();
+        encoder.push(self.version, SERDE_VERSION);
+        encoder.push(self.board.x, SERDE_BOARD);
+        encoder.push(self.board.y, SERDE_BOARD);
+        encoder.push(self.figures, SERDE_FIGURE_SET);
+        encoder.push(self.checksum, SERDE_CHECKSUM);
+        encoder.result()
     }
 
-    // Current score
-    encoder.push(score, SERDE_SCORE_SIZE);
+    fn decode(data: &str) -> Result<Header, SaveError> {
+        let mut decoder = Decoder::decode(data).map_err(|_| SaveError::BadSymbol)?;
+        let version = take(&mut decoder, SERDE_VERSION)?;
+        let board = coord!(take(&mut decoder, SERDE_BOARD)?, take(&mut decoder, SERDE_BOARD)?);
+        let figures = take(&mut decoder, SERDE_FIGURE_SET)?;
+        let checksum = take(&mut decoder, SERDE_CHECKSUM)?;
+        Ok(Header { version, board, figures, checksum })
+    }
+}
 
-    // Current game time
-    encoder.push(game_time.elapsed_seconds() as i64, SERDE_TIME_SIZE);
+// `<header>.<state>`, the checksum is made over the state text
+pub fn serialize(engine: &GameEngine, elapsed: u64, assists: Assists) -> String {
+    let mut encoder = Encoder::new();
 
-    // Padding
-    encoder.push(SERDE_V2_SUPPORT, SERDE_PADDING_SIZE);
+    // Score and time
+    encoder.push(engine.score(), SERDE_SCORE_SIZE);
+    encoder.push(engine.streak().min(u16::MAX as u32), SERDE_STREAK);
+    encoder.push(elapsed as i64, SERDE_TIME_SIZE);
 
-    // Field color data
-    encoder.push(color_data.len() as u8, SERDE_COLOR);
-    for (pos, color) in color_data {
+    // Random sequence
+    encoder.push(engine.seed(), SERDE_SEED);
+    encoder.push(engine.random_state(), SERDE_SEED);
+
+    // Game assists
+    encoder.push(assists.flags(), SERDE_FLAGS);
+    encoder.push(assists.hints.min(u16::MAX as u32), SERDE_HINTS);
+
+    // Figure policy and the rest of the bag
+    let bag = engine.basket().bag();
+    encoder.push(engine.policy().id(), SERDE_POLICY);
+    encoder.push(bag.len() as u16, SERDE_BAG_SIZE);
+    for &id in bag {
+        encoder.push(id, SERDE_FIGURE_SIZE);
+    }
+
+    // Game rules and board mask
+    encoder.push(engine.rules().flags(), SERDE_RULES);
+    encoder.push(engine.rules().mask, SERDE_MASK);
+
+    // Field blocks, basket figures, hold slot and preview
+    push_position(&mut encoder, engine);
+
+    let state = encoder.result();
+    let header = Header {
+        version: SERDE_VERSION_CURRENT,
+        board: engine.field().field_size,
+        figures: figures::set_id(engine.figures()),
+        checksum: codec::crc32(state.as_bytes()),
+    };
+    format!("{}{}{}", header.encode(), SERDE_SEPARATOR, state)
+}
+
+// `<header>.<position>` code of the field, block colours, basket and preview, score and time are not shared
+pub fn share_code(engine: &GameEngine) -> String {
+    let mut encoder = Encoder::new();
+    encoder.push(engine.rules().flags(), SERDE_RULES);
+    encoder.push(engine.rules().mask, SERDE_MASK);
+    push_position(&mut encoder, engine);
+
+    let position = encoder.result();
+    let header = Header {
+        version: SERDE_SHARE_VERSION,
+        board: engine.field().field_size,
+        figures: figures::set_id(engine.figures()),
+        checksum: codec::crc32(position.as_bytes()),
+    };
+    format!("{}{}{}", header.encode(), SERDE_SEPARATOR, position)
+}
+
+// position of the code replaces the field and the basket of the new game, the game is not changed on error
+pub fn load_code(code: &str, engine: &mut GameEngine) -> Result<(), SaveError> {
+    let (header, position) = code.trim().split_once(SERDE_SEPARATOR).ok_or(SaveError::BadSymbol)?;
+    let header = Header::decode(header)?;
+    if header.version != SERDE_SHARE_VERSION {
+        return Err(SaveError::UnknownVersion(header.version));
+    }
+    check_header(&header, position, engine)?;
+    let mut decoder = Decoder::decode(position).map_err(|_| SaveError::BadSymbol)?;
+
+    let mut restored = engine.clone();
+    restored.field_mut().clear();
+    restored.set_score(0);
+    restored.set_streak(0);
+    let flags = take(&mut decoder, SERDE_RULES)?;
+    restored.set_rules(restored.rules().with_flags(flags).ok_or(SaveError::BadRules(flags))?);
+    if take::<u32>(&mut decoder, SERDE_MASK)? != restored.rules().mask {
+        return Err(SaveError::OtherBoard);
+    }
+    restore_position(&mut decoder, &mut restored)?;
+    *engine = restored;
+    Ok(())
+}
+
+// field blocks, basket figures with the hold slot and preview
+fn push_position(encoder: &mut Encoder, engine: &GameEngine) {
+    let blocks = engine.field().blocks();
+    encoder.push(blocks.len() as u16, SERDE_BLOCKS);
+    for Block { pos, color } in blocks {
         encoder.push(pos.x, SERDE_POS);
         encoder.push(pos.y, SERDE_POS);
         encoder.push(color, SERDE_COLOR);
     }
 
-    encoder.result()
+    for index in 0..=HOLD_INDEX {
+        encoder.push(engine.basket().get(index).map_or(0, |figure| figure.index), SERDE_FIGURE_SIZE);
+        encoder.push(turns(engine, index), SERDE_TURNS);
+    }
+    let next = engine.basket().next();
+    for index in 0..engine.basket().len() {
+        encoder.push(next.get(index).map_or(0, |figure| figure.index), SERDE_FIGURE_SIZE);
+    }
+}
+
+// clockwise turns of the basket figure
+fn turns(engine: &GameEngine, index: usize) -> u8 {
+    let Some(figure) = engine.basket().get(index) else {
+        return 0;
+    };
+    engine.figure(figure.index).and_then(|base| figure.turns_from(base)).unwrap_or(0)
+}
+
+fn take<T>(decoder: &mut Decoder, size: u8) -> Result<T, SaveError>
+where
+    T: Default + std::ops::AddAssign<T> + std::ops::ShlAssign<usize> + From<u8> + Copy,
+{
+    decoder.take(size).map_err(|_| SaveError::Truncated)
+}
+
+// returns elapsed seconds of the game, state is changed only if everything is loaded and saves without
+// header are migrated on the next save
+pub fn deserialize(data: &str, engine: &mut GameEngine, assists: &mut Assists) -> Result<u64, SaveError> {
+    let mut restored = engine.clone();
+    // blocks of the current game are replaced
+    restored.field_mut().clear();
+    let mut restored_assists = Assists::default();
+    let elapsed = match data.trim().split_once(SERDE_SEPARATOR) {
+        Some((header, state)) => restore(header, state, &mut restored, &mut restored_assists)?,
+        None => restore_legacy(data.trim(), &mut restored)?,
+    };
+    *engine = restored;
+    *assists = restored_assists;
+    Ok(elapsed)
+}
+
+fn restore(header: &str, state: &str, engine: &mut GameEngine, assists: &mut Assists) -> Result<u64, SaveError> {
+    let header = Header::decode(header)?;
+    if header.version != SERDE_VERSION_CURRENT {
+        return Err(SaveError::UnknownVersion(header.version));
+    }
+    check_header(&header, state, engine)?;
+    let mut decoder = Decoder::decode(state).map_err(|_| SaveError::BadSymbol)?;
+
+    engine.set_score(take(&mut decoder, SERDE_SCORE_SIZE)?);
+    engine.set_streak(take(&mut decoder, SERDE_STREAK)?);
+    let elapsed = take(&mut decoder, SERDE_TIME_SIZE)?;
+
+    let seed = take(&mut decoder, SERDE_SEED)?;
+    engine.restore_random(seed, take(&mut decoder, SERDE_SEED)?);
+
+    assists.set_flags(take(&mut decoder, SERDE_FLAGS)?);
+    assists.hints = take(&mut decoder, SERDE_HINTS)?;
+
+    restore_policy(&mut decoder, engine)?;
+
+    let flags = take(&mut decoder, SERDE_RULES)?;
+    engine.set_rules(engine.rules().with_flags(flags).ok_or(SaveError::BadRules(flags))?);
+    if take::<u32>(&mut decoder, SERDE_MASK)? != engine.rules().mask {
+        return Err(SaveError::OtherBoard);
+    }
+
+    restore_position(&mut decoder, engine)?;
+    Ok(elapsed)
 }
 
-pub fn deserialize(
-    data: String, palette: &[Color], figures: &[Figure], field: &mut Field, basket: &mut BasketSystem, score: &mut u32,
-    game_time: &mut GameTime,
-) -> Option<()> {
-    let mut decoder = Decoder::decode(&data)?;
+fn check_header(header: &Header, data: &str, engine: &GameEngine) -> Result<(), SaveError> {
+    if header.checksum != codec::crc32(data.as_bytes()) {
+        return Err(SaveError::BadChecksum);
+    }
+    if header.figures != figures::set_id(engine.figures()) {
+        return Err(SaveError::OtherFigureSet);
+    }
+    if header.board != engine.field().field_size {
+        return Err(SaveError::OtherBoard);
+    }
+    Ok(())
+}
+
+fn restore_position(decoder: &mut Decoder, engine: &mut GameEngine) -> Result<(), SaveError> {
+    for _ in 0..take::<u16>(decoder, SERDE_BLOCKS)? {
+        let pos = coord!(take(decoder, SERDE_POS)?, take(decoder, SERDE_POS)?);
+        restore_block(engine, pos, take(decoder, SERDE_COLOR)?)?;
+    }
+
+    let mut basket = [0; HOLD_INDEX + 1];
+    let mut turns = [0; HOLD_INDEX + 1];
+    for index in 0..=HOLD_INDEX {
+        basket[index] = take(decoder, SERDE_FIGURE_SIZE)?;
+        turns[index] = take(decoder, SERDE_TURNS)?;
+    }
+    restore_basket(engine, basket, turns)?;
+    restore_next(decoder, engine)
+}
+
+// saves of the first versions without the header, the second one has block colors
+// and its version is kept in the padding bits
+fn restore_legacy(data: &str, engine: &mut GameEngine) -> Result<u64, SaveError> {
+    // first versions are made with built-in figures on the classic board
+    if figures::set_id(engine.figures()) != FigureSet::default().id() {
+        return Err(SaveError::OtherFigureSet);
+    }
+    if engine.field().field_size != Rules::default().board || engine.rules().mask != 0 {
+        return Err(SaveError::OtherBoard);
+    }
+    let mut decoder = Decoder::decode(data).map_err(|_| SaveError::BadSymbol)?;
 
-    // restore field
-    for y in 0..field.field_size.y {
-        for x in 0..field.field_size.x {
-            if decoder.take::<u8>(1)? == 1 {
-                field.set(coord!(x, y), palette[0]);
+    // restore field, colors are restored from color data
+    for y in 0..FIELD_LEN as i16 {
+        for x in 0..FIELD_LEN as i16 {
+            if take::<u8>(&mut decoder, SERDE_FIELD_SIZE)? == 1 {
+                engine.field_mut().set(coord!(x, y), 0);
             }
         }
     }
 
-    // restore figures
-    for index in 0..BASKET_COUNT as usize {
-        let fig_num = decoder.take::<usize>(SERDE_FIGURE_SIZE)?;
-        if fig_num > 0 {
-            basket.set(index, figures[fig_num - 1].clone());
-        } else {
-            basket.pop(index);
-        }
+    let mut basket = [0; HOLD_INDEX + 1];
+    for item in basket.iter_mut().take(BASKET_COUNT as usize) {
+        *item = take(&mut decoder, SERDE_FIGURE_SIZE)?;
     }
 
     // restore game score
-    *score = decoder.take(SERDE_SCORE_SIZE)?;
+    engine.set_score(take(&mut decoder, SERDE_SCORE_SIZE)?);
 
     // restore elapsed time
-    let elapsed = decoder.take::<u64>(SERDE_TIME_SIZE)?;
-    game_time.update(elapsed);
+    let elapsed = take(&mut decoder, SERDE_TIME_SIZE)?;
 
-    let padding = decoder.take::<u8>(SERDE_PADDING_SIZE)?;
     // load extra info about field colors
-    if padding == SERDE_V2_SUPPORT {
-        for _ in 0..decoder.take::<u8>(SERDE_COLOR)? {
-            let x = decoder.take::<i16>(SERDE_POS)?;
-            let y = decoder.take::<i16>(SERDE_POS)?;
-            let color = decoder.take::<usize>(SERDE_COLOR)?;
-            field.set(coord!(x, y), palette[color]);
+    if take::<u8>(&mut decoder, SERDE_PADDING_SIZE)? == SERDE_V2_SUPPORT {
+        for _ in 0..take::<u8>(&mut decoder, SERDE_COLOR)? {
+            let pos = coord!(take(&mut decoder, SERDE_POS)?, take(&mut decoder, SERDE_POS)?);
+            let color = take(&mut decoder, SERDE_COLOR)?;
+            // blocks of the field are already set
+            engine.field_mut().unset(&pos);
+            restore_block(engine, pos, color)?;
         }
     }
 
-    Some(())
+    // uniform policy and classic rules, preview is rolled from the random state
+    engine.basket_mut().restore_policy(Policy::Uniform, Vec::new());
+    engine.set_rules(Rules { fair: false, rotation: false, hold: false, scoring: Scoring::Classic, ..engine.rules() });
+    restore_basket(engine, basket, [0; HOLD_INDEX + 1])?;
+    engine.basket_mut().set_next(Vec::new());
+    engine.preroll();
+    Ok(elapsed)
+}
+
+fn restore_block(engine: &mut GameEngine, pos: Coord, color: u8) -> Result<(), SaveError> {
+    if color >= FIGURE_COLORS {
+        return Err(SaveError::BadColor(color));
+    }
+    if !engine.field().is_free(&pos) {
+        return Err(SaveError::BadCell(pos));
+    }
+    engine.field_mut().set(pos, color);
+    Ok(())
+}
+
+fn restore_policy(decoder: &mut Decoder, engine: &mut GameEngine) -> Result<(), SaveError> {
+    let id = take(decoder, SERDE_POLICY)?;
+    let policy = Policy::from_id(id).ok_or(SaveError::BadPolicy(id))?;
+    let mut bag = Vec::new();
+    for _ in 0..take::<u16>(decoder, SERDE_BAG_SIZE)? {
+        let id = take(decoder, SERDE_FIGURE_SIZE)?;
+        engine.figure(id).ok_or(SaveError::BadFigure(id))?;
+        bag.push(id);
+    }
+    engine.basket_mut().restore_policy(policy, bag);
+    Ok(())
+}
+
+fn restore_basket(
+    engine: &mut GameEngine, basket: [u8; HOLD_INDEX + 1], turns: [u8; HOLD_INDEX + 1],
+) -> Result<(), SaveError> {
+    for (index, id) in basket.into_iter().enumerate() {
+        if id > 0 {
+            let figure = engine.figure(id).ok_or(SaveError::BadFigure(id))?.clone();
+            engine.basket_mut().set(index, figure);
+            for _ in 0..turns[index] {
+                engine.rotate(index);
+            }
+        } else {
+            engine.basket_mut().pop(index);
+        }
+    }
+    Ok(())
+}
+
+fn restore_next(decoder: &mut Decoder, engine: &mut GameEngine) -> Result<(), SaveError> {
+    let mut next = Vec::new();
+    for _ in 0..BASKET_COUNT {
+        let id = take(decoder, SERDE_FIGURE_SIZE)?;
+        if id > 0 {
+            next.push(engine.figure(id).ok_or(SaveError::BadFigure(id))?.clone());
+        }
+    }
+    engine.basket_mut().set_next(next);
+    engine.preroll();
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::BoardMask;
+    use crate::engine::default_figures;
+
+    // saved by the first versions: blocks in the bottom row and two corners, figures 3 and 12 in the basket
+    const LEGACY_SAVE: &str = "aeaaaaaaaqaaaaa+aygaaaatyaaaaaaaaaV1b5ahvqajaHKckqi5aKKc";
+    // the same field without colors, figures 2 and 7
+    const LEGACY_SAVE_V1: &str = "aeaaaaaaaqaaaaa+arWaaaafaaaaaaaaaaga";
+
+    fn load(data: &str) -> Result<(GameEngine, u64, Assists), SaveError> {
+        let mut engine = GameEngine::new(default_figures(), 1);
+        let mut assists = Assists::default();
+        let elapsed = deserialize(data, &mut engine, &mut assists)?;
+        Ok((engine, elapsed, assists))
+    }
+
+    #[test]
+    fn migrate_legacy() {
+        let (engine, elapsed, assists) = load(LEGACY_SAVE).unwrap();
+        assert_eq!((engine.score(), engine.field().blocks().len(), elapsed), (155, 7, 95));
+        assert!(engine.field().blocks().contains(&Block { pos: coord!(9, 0), color: 7 }));
+        let ids = |engine: &GameEngine| engine.basket().figures().iter().map(|figure| figure.index).collect::<Vec<_>>();
+        assert_eq!(ids(&engine), vec![3, 12]);
+
+        let (first, elapsed_v1, _) = load(LEGACY_SAVE_V1).unwrap();
+        assert_eq!((first.score(), elapsed_v1, ids(&first)), (40, 12, vec![2, 7]));
+        assert!(first.field().blocks().iter().all(|block| block.color == 0));
+        assert_eq!(first.field().blocks().len(), engine.field().blocks().len());
+
+        // the same game in the current format
+        let data = serialize(&engine, elapsed, assists);
+        assert!(data.contains(SERDE_SEPARATOR));
+        let (restored, _, _) = load(&data).unwrap();
+        assert_eq!(restored.field().blocks(), engine.field().blocks());
+        assert_eq!(restored.basket().figures(), engine.basket().figures());
+        assert_eq!(restored.basket().next(), engine.basket().next());
+        assert_eq!(restored.random_state(), engine.random_state());
+
+        // first versions are played on the classic board only
+        let mut wide = GameEngine::with_board(default_figures(), 1, Rules::default(), &BoardMask::new(coord!(12, 10)));
+        assert_eq!(deserialize(LEGACY_SAVE, &mut wide, &mut Assists::default()), Err(SaveError::OtherBoard));
+    }
+
+    #[test]
+    fn broken_saves() {
+        let (engine, elapsed, assists) = load(LEGACY_SAVE).unwrap();
+        let data = serialize(&engine, elapsed, assists);
+        // cut saves are reported, not panicked on
+        for text in [LEGACY_SAVE, data.as_str()] {
+            for size in 0..text.len() - 1 {
+                assert!(load(&text[..size]).is_err(), "{}", &text[..size]);
+            }
+        }
+        let (header, state) = data.split_once(SERDE_SEPARATOR).unwrap();
+        let state = state.replacen('a', "b", 1);
+        assert_eq!(load(&format!("{}.{}", header, state)).err(), Some(SaveError::BadChecksum));
+        assert_eq!(load(&format!("zz{}", &data[2..])).err(), Some(SaveError::UnknownVersion(101)));
+        assert_eq!(load("ab#c").err(), Some(SaveError::BadSymbol));
+    }
+
+    #[test]
+    fn share_codes() {
+        let (engine, elapsed, assists) = load(LEGACY_SAVE).unwrap();
+        let code = share_code(&engine);
+        assert!(code.len() < serialize(&engine, elapsed, assists).len());
+
+        // position goes to the new game, its score starts from zero
+        let mut practice = GameEngine::new(default_figures(), 2);
+        load_code(&code, &mut practice).unwrap();
+        assert_eq!(practice.field().blocks(), engine.field().blocks());
+        assert_eq!(practice.basket().figures(), engine.basket().figures());
+        assert_eq!(practice.basket().next(), engine.basket().next());
+        assert_eq!((practice.score(), practice.seed()), (0, 2));
+        assert_eq!(share_code(&practice), code);
+
+        // codes and saves are not loaded in place of each other
+        let save = serialize(&engine, elapsed, assists);
+        assert_eq!(load_code(&save, &mut practice), Err(SaveError::UnknownVersion(SERDE_VERSION_CURRENT)));
+        assert_eq!(load(&code).err(), Some(SaveError::UnknownVersion(SERDE_SHARE_VERSION)));
+        for size in 0..code.len() - 1 {
+            assert!(load_code(&code[..size], &mut practice).is_err(), "{}", &code[..size]);
+        }
+    }
 }
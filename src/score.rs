@@ -1,10 +1,38 @@
+use std::collections::HashMap;
+
 use tini::Ini;
 
+use rs_1010::coord::Coord;
+use rs_1010::daily::{self, Date};
+use rs_1010::engine::{self, Rules};
+use rs_1010::scoring::Scoring;
+
+use crate::consts::{BOARD_MARK, FAIR_MARK, HINT_MARK, HOLD_MARK, MASK_MARK, ROTATION_MARK, SCORING_MARK, UNDO_MARK};
+use crate::game::Mode;
+use rs_1010::save::Assists;
+
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct Score {
     pub name: String,
     pub score: u32,
     pub time: String,
+    // undo was used in the game
+    pub undo: bool,
+    // shown hints count
+    pub hints: u32,
+    // game with fair refill rule
+    pub fair: bool,
+    // game with rotation rule
+    pub rotation: bool,
+    // game with hold slot
+    pub hold: bool,
+    // field width and height
+    pub board: Coord,
+    // id of the board mask
+    pub mask: u32,
+    pub scoring: Scoring,
+    // every mode has own leaderboard
+    pub mode: Mode,
     pub last: bool,
 }
 
@@ -13,9 +41,60 @@ pub struct ScoreTable {
     users: Vec<Score>,
 }
 
+// results of the daily challenge, only the first game of the day is kept
+#[derive(Debug)]
+pub struct DailyHistory {
+    days: Vec<(Date, u32)>,
+}
+
 impl Score {
-    fn new(name: String, score: u32, time: String, last: bool) -> Score {
-        Score { name, score, time, last }
+    fn new(name: String, score: u32, time: String, assists: Assists, rules: Rules, mode: Mode, last: bool) -> Score {
+        let Assists { undo, hints } = assists;
+        let Rules { fair, rotation, hold, board, mask, scoring, .. } = rules;
+        Score { name, score, time, undo, hints, fair, rotation, hold, board, mask, scoring, mode, last }
+    }
+
+    // scores with other rules are not compared
+    fn same_rules(&self, rules: Rules) -> bool {
+        self.fair == rules.fair
+            && self.rotation == rules.rotation
+            && self.hold == rules.hold
+            && self.board == rules.board
+            && self.mask == rules.mask
+            && self.scoring == rules.scoring
+    }
+
+    // short marks of the game rules and assists
+    pub fn marks(&self) -> String {
+        let mut marks = String::new();
+        if self.fair {
+            marks.push(FAIR_MARK);
+        }
+        if self.rotation {
+            marks.push(ROTATION_MARK);
+        }
+        if self.hold {
+            marks.push(HOLD_MARK);
+        }
+        if self.board != Rules::default().board {
+            marks.push(BOARD_MARK);
+            marks.push_str(&board_name(self.board));
+        }
+        if self.mask != 0 {
+            marks.push(MASK_MARK);
+        }
+        if self.scoring != Scoring::Classic {
+            marks.push(SCORING_MARK);
+            marks.push_str(self.scoring.name());
+        }
+        if self.undo {
+            marks.push(UNDO_MARK);
+        }
+        if self.hints > 0 {
+            marks.push(HINT_MARK);
+            marks.push_str(&self.hints.to_string());
+        }
+        marks
     }
 }
 
@@ -24,21 +103,44 @@ impl ScoreTable {
         let user: Vec<String> = config.get_vec("score", "users").unwrap_or_default();
         let score: Vec<u32> = config.get_vec("score", "scores").unwrap_or_default();
         let time: Vec<String> = config.get_vec("score", "times").unwrap_or_default();
+        // older configs have no assist marks
+        let undo: Vec<bool> = config.get_vec("score", "undo").unwrap_or_default();
+        let hints: Vec<u32> = config.get_vec("score", "hints").unwrap_or_default();
+        let fair: Vec<bool> = config.get_vec("score", "fair").unwrap_or_default();
+        let rotation: Vec<bool> = config.get_vec("score", "rotation").unwrap_or_default();
+        let hold: Vec<bool> = config.get_vec("score", "hold").unwrap_or_default();
+        let board: Vec<String> = config.get_vec("score", "board").unwrap_or_default();
+        let mask: Vec<u32> = config.get_vec("score", "mask").unwrap_or_default();
+        let mode: Vec<String> = config.get_vec("score", "mode").unwrap_or_default();
+        let scoring: Vec<String> = config.get_vec("score", "scoring").unwrap_or_default();
         let mut users = Vec::new();
-        for (u, s, t) in user.into_iter().zip(score).zip(time).map(|((x, y), z)| (x, y, z)) {
-            users.push(Score::new(u, s, t, false));
+        for (index, (u, s, t)) in user.into_iter().zip(score).zip(time).map(|((x, y), z)| (x, y, z)).enumerate() {
+            let assists = Assists {
+                undo: undo.get(index).copied().unwrap_or(false),
+                hints: hints.get(index).copied().unwrap_or(0),
+            };
+            let rules = Rules {
+                fair: fair.get(index).copied().unwrap_or(false),
+                rotation: rotation.get(index).copied().unwrap_or(false),
+                hold: hold.get(index).copied().unwrap_or(false),
+                board: board
+                    .get(index)
+                    .and_then(|value| engine::parse_board(value).ok())
+                    .unwrap_or(Rules::default().board),
+                mask: mask.get(index).copied().unwrap_or(0),
+                scoring: scoring.get(index).and_then(|name| name.parse().ok()).unwrap_or_default(),
+                ..Rules::default()
+            };
+            let mode = mode.get(index).and_then(|name| Mode::parse(name)).unwrap_or(Mode::Endless);
+            users.push(Score::new(u, s, t, assists, rules, mode, false));
         }
         let mut game_table = ScoreTable { users };
         game_table.sort_by_score();
         game_table
     }
 
-    pub fn get_highscore(&self) -> u32 {
-        if self.users.is_empty() {
-            0
-        } else {
-            self.users[0].score
-        }
+    pub fn get_highscore(&self, rules: Rules, mode: Mode) -> u32 {
+        self.users.iter().find(|user| user.mode == mode && user.same_rules(rules)).map_or(0, |user| user.score)
     }
 
     pub fn update_config(mut self, count: usize, config: Ini) -> Ini {
@@ -46,31 +148,126 @@ impl ScoreTable {
         let mut users = Vec::new();
         let mut scores = Vec::new();
         let mut times = Vec::new();
-        for Score { name, score, time, .. } in self.users.into_iter().take(count) {
+        let mut undo = Vec::new();
+        let mut hints = Vec::new();
+        let mut fair = Vec::new();
+        let mut rotation = Vec::new();
+        let mut hold = Vec::new();
+        let mut board = Vec::new();
+        let mut mask = Vec::new();
+        let mut mode = Vec::new();
+        let mut scoring = Vec::new();
+        // the best scores of every mode are kept
+        let mut kept = HashMap::new();
+        let users_of_mode = self.users.into_iter().filter(|user| {
+            let kept = kept.entry(user.mode).or_insert(0);
+            *kept += 1;
+            *kept <= count
+        });
+        for Score {
+            name,
+            score,
+            time,
+            undo: u,
+            hints: h,
+            fair: f,
+            rotation: r,
+            hold: o,
+            board: b,
+            mask: m,
+            scoring: s,
+            mode: g,
+            ..
+        } in users_of_mode
+        {
             users.push(name);
             scores.push(format!("{}", score));
             times.push(time);
+            undo.push(format!("{}", u));
+            hints.push(format!("{}", h));
+            fair.push(format!("{}", f));
+            rotation.push(format!("{}", r));
+            hold.push(format!("{}", o));
+            board.push(board_name(b));
+            mask.push(format!("{}", m));
+            mode.push(g.name());
+            scoring.push(s.name());
         }
         config
             .section("score")
             .item("users", users.as_slice().join(","))
             .item("scores", scores.as_slice().join(","))
             .item("times", times.as_slice().join(","))
+            .item("undo", undo.as_slice().join(","))
+            .item("hints", hints.as_slice().join(","))
+            .item("fair", fair.as_slice().join(","))
+            .item("rotation", rotation.as_slice().join(","))
+            .item("hold", hold.as_slice().join(","))
+            .item("board", board.as_slice().join(","))
+            .item("mask", mask.as_slice().join(","))
+            .item("mode", mode.as_slice().join(","))
+            .item("scoring", scoring.as_slice().join(","))
     }
 
-    pub fn push(&mut self, name: String, score: u32, time: String) {
+    pub fn push(&mut self, name: String, score: u32, time: String, assists: Assists, rules: Rules, mode: Mode) {
         for item in self.users.iter_mut() {
             item.last = false;
         }
-        self.users.push(Score::new(name, score, time, true));
+        self.users.push(Score::new(name, score, time, assists, rules, mode, true));
         self.sort_by_score();
     }
 
-    pub fn iter(&self) -> std::slice::Iter<Score> {
+    pub fn iter(&self) -> std::slice::Iter<'_, Score> {
         self.users.iter()
     }
 
     fn sort_by_score(&mut self) {
-        self.users.sort_by(|b, a| a.score.cmp(&b.score));
+        self.users.sort_by_key(|a| std::cmp::Reverse(a.score));
     }
 }
+
+impl DailyHistory {
+    pub fn from_config(config: &Ini) -> DailyHistory {
+        let dates: Vec<String> = config.get_vec("daily", "dates").unwrap_or_default();
+        let scores: Vec<u32> = config.get_vec("daily", "scores").unwrap_or_default();
+        let mut days: Vec<_> =
+            dates.iter().zip(scores).filter_map(|(date, score)| Some((date.parse().ok()?, score))).collect();
+        days.sort_by_key(|&(date, _)| std::cmp::Reverse(date));
+        DailyHistory { days }
+    }
+
+    pub fn update_config(&self, config: Ini) -> Ini {
+        let dates: Vec<_> = self.days.iter().map(|(date, _)| date.to_string()).collect();
+        let scores: Vec<_> = self.days.iter().map(|(_, score)| score.to_string()).collect();
+        config.section("daily").item("dates", dates.join(",")).item("scores", scores.join(","))
+    }
+
+    pub fn get(&self, date: Date) -> Option<u32> {
+        self.days.iter().find(|(day, _)| *day == date).map(|&(_, score)| score)
+    }
+
+    // returns false if the day is already played
+    pub fn push(&mut self, date: Date, score: u32) -> bool {
+        if self.get(date).is_some() {
+            return false;
+        }
+        self.days.push((date, score));
+        self.days.sort_by_key(|&(date, _)| std::cmp::Reverse(date));
+        true
+    }
+
+    pub fn streak(&self, today: Date) -> u32 {
+        let dates: Vec<_> = self.days.iter().map(|&(date, _)| date).collect();
+        daily::streak(&dates, today)
+    }
+
+    // the last days first
+    pub fn iter(&self) -> std::slice::Iter<'_, (Date, u32)> {
+        self.days.iter()
+    }
+}
+
+// board size as `<width>x<height>`
+fn board_name(board: Coord) -> String {
+    format!("{}x{}", board.x, board.y)
+}
@@ -0,0 +1,132 @@
+use std::fmt;
+use std::str::FromStr;
+
+use crate::engine::Lines;
+
+// game score multiplier
+pub const LINE_MULTIPLIER: u32 = 30;
+pub const BLOCK_COST_MULTIPLIER: u32 = 5;
+// line score is multiplied by the clear streak, but not more than this
+pub const MAX_STREAK_MULTIPLIER: u32 = 4;
+// no blocks are left on the field after the clear
+pub const PERFECT_CLEAR_BONUS: u32 = 10 * LINE_MULTIPLIER;
+
+// how placed figures and removed lines are scored
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Scoring {
+    // blocks and lines as in the first versions
+    #[default]
+    Classic,
+    // clears in consecutive moves multiply the line score
+    Streak,
+    // streak and bonus for the empty field
+    Combo,
+}
+
+pub const SCORINGS: [Scoring; 3] = [Scoring::Classic, Scoring::Streak, Scoring::Combo];
+
+// score of one move
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Points {
+    pub blocks: u32,
+    pub lines: u32,
+    // moves with a clear in a row, this one included
+    pub streak: u32,
+    // extra line score of the streak
+    pub combo: u32,
+    pub perfect: u32,
+}
+
+impl Scoring {
+    pub fn id(self) -> u8 {
+        match self {
+            Scoring::Classic => 0,
+            Scoring::Streak => 1,
+            Scoring::Combo => 2,
+        }
+    }
+
+    pub fn from_id(id: u8) -> Option<Scoring> {
+        SCORINGS.into_iter().find(|scoring| scoring.id() == id)
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Scoring::Classic => "classic",
+            Scoring::Streak => "streak",
+            Scoring::Combo => "combo",
+        }
+    }
+
+    // `streak` is a number of moves with a clear in a row, `perfect` is the empty field after the move
+    pub fn points(self, blocks: u32, lines: Lines, streak: u32, perfect: bool) -> Points {
+        let mut points = Points {
+            blocks: blocks * BLOCK_COST_MULTIPLIER,
+            lines: (lines.x + lines.y + lines.x * lines.y) * LINE_MULTIPLIER,
+            streak,
+            ..Points::default()
+        };
+        if self != Scoring::Classic {
+            points.combo = points.lines * (streak.clamp(1, MAX_STREAK_MULTIPLIER) - 1);
+        }
+        if self == Scoring::Combo && perfect && lines.not_empty() {
+            points.perfect = PERFECT_CLEAR_BONUS;
+        }
+        points
+    }
+}
+
+impl Points {
+    pub fn total(&self) -> u32 {
+        self.blocks + self.lines + self.combo + self.perfect
+    }
+}
+
+impl FromStr for Scoring {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Scoring, String> {
+        SCORINGS
+            .into_iter()
+            .find(|scoring| scoring.name() == value)
+            .ok_or_else(|| format!("unknown scoring `{}`", value))
+    }
+}
+
+impl fmt::Display for Scoring {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_and_ids() {
+        for scoring in SCORINGS {
+            assert_eq!(scoring.name().parse(), Ok(scoring));
+            assert_eq!(Scoring::from_id(scoring.id()), Some(scoring));
+        }
+        assert!("arcade".parse::<Scoring>().is_err());
+    }
+
+    #[test]
+    fn rule_sets() {
+        let cross = Lines { x: 1, y: 1 };
+        // classic score does not depend on the streak
+        let classic = Scoring::Classic.points(5, cross, 3, true);
+        assert_eq!(classic.total(), 5 * BLOCK_COST_MULTIPLIER + 3 * LINE_MULTIPLIER);
+
+        let streak = Scoring::Streak.points(5, cross, 3, true);
+        assert_eq!(streak.combo, 2 * streak.lines);
+        assert_eq!(streak.perfect, 0);
+        assert_eq!(Scoring::Streak.points(5, cross, 9, false).combo, 3 * streak.lines);
+
+        let combo = Scoring::Combo.points(5, cross, 1, true);
+        assert_eq!(combo.total(), classic.total() + PERFECT_CLEAR_BONUS);
+        // placed figure without a clear
+        assert_eq!(Scoring::Combo.points(1, Lines::empty(), 0, false).total(), BLOCK_COST_MULTIPLIER);
+    }
+}
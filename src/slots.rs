@@ -0,0 +1,175 @@
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use crate::daily::Date;
+
+pub const SLOT_EXT: &str = "sav";
+// mode name of the daily challenge
+pub const DAILY_MODE: &str = "daily";
+
+// saved game in its own file, `state` is the `save::serialize` string
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Slot {
+    pub name: String,
+    // name of the game mode
+    pub mode: String,
+    pub score: u32,
+    // game time in seconds
+    pub elapsed: u64,
+    pub date: Date,
+    // snapshot of the finished game, it is only viewed
+    pub over: bool,
+    pub state: String,
+}
+
+impl Slot {
+    // file of the slot in the saves directory
+    pub fn file_name(name: &str) -> String {
+        let stem: String =
+            name.chars().map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_lowercase() } else { '_' }).collect();
+        format!("{}.{}", stem, SLOT_EXT)
+    }
+
+    // loaded game is played for the scores, daily challenge is continued only from its autosave,
+    // so a slot saved before the end does not give the second attempt of the day
+    pub fn is_scored(&self) -> bool {
+        !self.over && self.mode != DAILY_MODE
+    }
+
+    pub fn path<P: AsRef<Path>>(&self, dir: P) -> PathBuf {
+        dir.as_ref().join(Slot::file_name(&self.name))
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, dir: P) -> io::Result<()> {
+        fs::create_dir_all(&dir)?;
+        fs::write(self.path(dir), self.to_string())
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Slot, String> {
+        let data = fs::read_to_string(path).map_err(|e| e.to_string())?;
+        data.parse()
+    }
+}
+
+// `<keyword> <value>` lines, all keywords are required
+impl FromStr for Slot {
+    type Err = String;
+
+    fn from_str(data: &str) -> Result<Slot, String> {
+        let (mut name, mut mode, mut score, mut elapsed, mut date, mut over, mut state) =
+            (None, None, None, None, None, None, None);
+        for (number, text) in data.lines().enumerate() {
+            let text = text.trim();
+            if text.is_empty() {
+                continue;
+            }
+            let (keyword, value) = text.split_once(' ').unwrap_or((text, ""));
+            let value = value.trim();
+            let error = |what: &str| format!("line {}: bad {} `{}`", number + 1, what, value);
+            match keyword {
+                "name" => name = Some(value.to_string()),
+                "mode" => mode = Some(value.to_string()),
+                "score" => score = Some(value.parse().map_err(|_| error("score"))?),
+                "elapsed" => elapsed = Some(value.parse().map_err(|_| error("time"))?),
+                "date" => date = Some(value.parse().map_err(|_| error("date"))?),
+                "over" => over = Some(value.parse().map_err(|_| error("flag"))?),
+                "state" => state = Some(value.to_string()),
+                _ => return Err(format!("line {}: unknown keyword `{}`", number + 1, keyword)),
+            }
+        }
+        let missing = |keyword: &str| format!("missing `{}`", keyword);
+        Ok(Slot {
+            name: name.filter(|name| !name.is_empty()).ok_or_else(|| missing("name"))?,
+            mode: mode.ok_or_else(|| missing("mode"))?,
+            score: score.ok_or_else(|| missing("score"))?,
+            elapsed: elapsed.ok_or_else(|| missing("elapsed"))?,
+            date: date.ok_or_else(|| missing("date"))?,
+            over: over.ok_or_else(|| missing("over"))?,
+            state: state.ok_or_else(|| missing("state"))?,
+        })
+    }
+}
+
+impl fmt::Display for Slot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "name {}", self.name)?;
+        writeln!(f, "mode {}", self.mode)?;
+        writeln!(f, "score {}", self.score)?;
+        writeln!(f, "elapsed {}", self.elapsed)?;
+        writeln!(f, "date {}", self.date)?;
+        writeln!(f, "over {}", self.over)?;
+        writeln!(f, "state {}", self.state)
+    }
+}
+
+// slots of the directory sorted by the file name, broken files are returned with the error
+pub fn list<P: AsRef<Path>>(dir: P) -> Vec<(PathBuf, Result<Slot, String>)> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut files: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == SLOT_EXT))
+        .collect();
+    files.sort();
+    files.into_iter().map(|path| (path.clone(), Slot::load(path))).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn slot() -> Slot {
+        Slot {
+            name: "Slot 2: before the cross".to_string(),
+            mode: "timed5".to_string(),
+            score: 1250,
+            elapsed: 3725,
+            date: Date { year: 2024, month: 2, day: 29 },
+            over: true,
+            state: "AAEA.AAAB".to_string(),
+        }
+    }
+
+    #[test]
+    fn text_round_trip() {
+        let slot = slot();
+        assert_eq!(slot.to_string().parse(), Ok(slot.clone()));
+        assert_eq!(Slot::file_name(&slot.name), "slot_2__before_the_cross.sav");
+
+        let text = slot.to_string();
+        assert!(text.replace("score 1250", "score many").parse::<Slot>().is_err());
+        assert!(text.replace("over true", "over").parse::<Slot>().is_err());
+        assert!(text.replace("date ", "day ").parse::<Slot>().is_err());
+        assert_eq!(text.replace("name Slot 2: before the cross\n", "").parse::<Slot>(), Err("missing `name`".into()));
+    }
+
+    #[test]
+    fn scored_slots() {
+        let mut slot = slot();
+        assert!(!slot.is_scored());
+        slot.over = false;
+        assert!(slot.is_scored());
+        slot.mode = DAILY_MODE.to_string();
+        assert!(!slot.is_scored());
+    }
+
+    #[test]
+    fn directory() {
+        let dir = std::env::temp_dir().join(format!("rs-1010-slots-{}", std::process::id()));
+        let slot = slot();
+        slot.save(&dir).unwrap();
+        fs::write(dir.join("broken.sav"), "name broken\n").unwrap();
+        fs::write(dir.join("notes.txt"), "not a slot").unwrap();
+
+        let slots = list(&dir);
+        assert_eq!(slots.len(), 2);
+        assert!(slots[0].1.is_err());
+        assert_eq!(slots[1], (slot.path(&dir), Ok(slot)));
+        fs::remove_dir_all(&dir).unwrap();
+        assert!(list(&dir).is_empty());
+    }
+}